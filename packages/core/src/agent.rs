@@ -5,6 +5,7 @@
 use std::collections::HashMap;
 use std::future::Future;
 use std::net::IpAddr;
+use std::net::SocketAddr;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::sync::Mutex;
@@ -15,9 +16,13 @@ use std::sync::atomic::Ordering;
 use std::time::Duration;
 
 use futures::StreamExt;
+use indexmap::IndexMap;
 use reqwest::Client;
+use reqwest::Url;
 use tokio::select;
 use tokio::sync::Notify;
+use tokio::sync::Semaphore;
+use tokio::sync::SemaphorePermit;
 use tokio::time::timeout;
 use tokio_util::sync::CancellationToken;
 
@@ -35,6 +40,7 @@ use crate::dispatcher::PauseState;
 use crate::dispatcher::RequestController;
 use crate::dispatcher::ResponseStart;
 use crate::error::CoreError;
+use crate::error::cap_message_len;
 
 /// reqwest exposes Happy-Eyeballs (parallel IPv4/IPv6 connect attempts) only
 /// when its `hickory-dns` resolver is enabled; this turns both off together.
@@ -49,6 +55,163 @@ fn configure_happy_eyeballs(
     }
 }
 
+fn configure_http2(
+    mut builder: reqwest::ClientBuilder,
+    config: &AgentConfig,
+) -> Result<reqwest::ClientBuilder, CoreError> {
+    if config.http2_prior_knowledge {
+        if !config.allow_h2 {
+            return Err(CoreError::InvalidArgument(
+                "http2PriorKnowledge requires allowH2".into(),
+            ));
+        }
+        builder = builder.http2_prior_knowledge();
+    } else if !config.allow_h2 {
+        return Ok(builder.http1_only());
+    }
+
+    if let Some(interval) = config.http2_keep_alive_interval {
+        builder = builder.http2_keep_alive_interval(interval);
+    }
+    if let Some(timeout) = config.http2_keep_alive_timeout {
+        builder = builder.http2_keep_alive_timeout(timeout);
+    }
+    Ok(builder.http2_keep_alive_while_idle(config.http2_keep_alive_while_idle))
+}
+
+fn configure_tls(
+    mut builder: reqwest::ClientBuilder,
+    config: &AgentConfig,
+) -> Result<reqwest::ClientBuilder, CoreError> {
+    if !config.reject_unauthorized {
+        builder = builder.danger_accept_invalid_certs(true);
+    }
+    if !config.reject_invalid_hostnames {
+        builder = builder.danger_accept_invalid_hostnames(true);
+    }
+
+    for pem in &config.ca {
+        // `from_pem_bundle` accepts both single PEM certs and multi-cert
+        // bundles (e.g. `/etc/ssl/certs/ca-certificates.crt`); `from_pem`
+        // alone parses just the first block and silently drops the rest,
+        // which broke the mitmproxy CI test that hands us the full system
+        // bundle.
+        let certs = reqwest::Certificate::from_pem_bundle(pem.as_bytes())
+            .map_err(|_| CoreError::InvalidArgument("invalid CA certificate".into()))?;
+        for cert in certs {
+            builder = builder.add_root_certificate(cert);
+        }
+    }
+
+    if let Some(identity) = &config.identity {
+        // rustls wants key and chain in one buffer; the newline guards
+        // against a cert PEM missing its trailing line break.
+        let pem = format!("{}\n{}", identity.cert, identity.key);
+        let identity = reqwest::Identity::from_pem(pem.as_bytes()).map_err(|_| {
+            CoreError::InvalidArgument(
+                "invalid client identity: expected PEM certificate and private key".into(),
+            )
+        })?;
+        builder = builder.identity(identity);
+    }
+    Ok(builder)
+}
+
+fn configure_proxy(
+    builder: reqwest::ClientBuilder,
+    proxy: &ProxyConfig,
+) -> Result<reqwest::ClientBuilder, CoreError> {
+    match proxy {
+        ProxyConfig::None => Ok(builder.no_proxy()),
+        // reqwest with the `system-proxy` feature reads
+        // HTTP_PROXY / HTTPS_PROXY / NO_PROXY automatically.
+        ProxyConfig::System => Ok(builder),
+        ProxyConfig::Custom {
+            uri,
+            headers,
+            auth,
+            no_proxy,
+        } => {
+            let mut proxy = reqwest::Proxy::all(uri)
+                .map_err(|e| CoreError::InvalidArgument(format!("invalid proxy URI: {e}")))?;
+            if !no_proxy.is_empty() {
+                // Same grammar as `NO_PROXY`: hosts, `.domain` suffixes, IPs, CIDRs.
+                proxy = proxy.no_proxy(reqwest::NoProxy::from_string(&no_proxy.join(",")));
+            }
+            if let Some(auth) = auth {
+                proxy = proxy.basic_auth(&auth.username, &auth.password);
+            }
+            if !headers.is_empty() {
+                proxy = proxy.headers(header_map(headers, "proxy")?);
+            }
+            Ok(builder.proxy(proxy))
+        },
+    }
+}
+
+/// Apply the per-codec response decompression toggles. Asking for a codec
+/// whose crate feature is compiled out is an error rather than a silent
+/// no-op that would hand callers still-encoded bodies.
+fn configure_decompression(
+    builder: reqwest::ClientBuilder,
+    config: &AgentConfig,
+) -> Result<reqwest::ClientBuilder, CoreError> {
+    #[cfg(feature = "gzip")]
+    let builder = builder.gzip(config.gzip);
+    #[cfg(not(feature = "gzip"))]
+    if config.gzip {
+        return Err(CoreError::InvalidArgument(
+            "gzip: support not compiled in (enable the `gzip` feature)".into(),
+        ));
+    }
+
+    #[cfg(feature = "brotli")]
+    let builder = builder.brotli(config.brotli);
+    #[cfg(not(feature = "brotli"))]
+    if config.brotli {
+        return Err(CoreError::InvalidArgument(
+            "brotli: support not compiled in (enable the `brotli` feature)".into(),
+        ));
+    }
+
+    #[cfg(feature = "deflate")]
+    let builder = builder.deflate(config.deflate);
+    #[cfg(not(feature = "deflate"))]
+    if config.deflate {
+        return Err(CoreError::InvalidArgument(
+            "deflate: support not compiled in (enable the `deflate` feature)".into(),
+        ));
+    }
+
+    #[cfg(feature = "zstd")]
+    let builder = builder.zstd(config.zstd);
+    #[cfg(not(feature = "zstd"))]
+    if config.zstd {
+        return Err(CoreError::InvalidArgument(
+            "zstd: support not compiled in (enable the `zstd` feature)".into(),
+        ));
+    }
+
+    Ok(builder)
+}
+
+/// Build a `HeaderMap` from caller-supplied pairs; `label` names the option
+/// in the error message.
+fn header_map(
+    headers: &HashMap<String, String>,
+    label: &str,
+) -> Result<reqwest::header::HeaderMap, CoreError> {
+    let mut hmap = reqwest::header::HeaderMap::with_capacity(headers.len());
+    for (k, v) in headers {
+        let name = reqwest::header::HeaderName::from_bytes(k.as_bytes())
+            .map_err(|_| CoreError::InvalidArgument(format!("invalid {label} header name")))?;
+        let value = reqwest::header::HeaderValue::from_str(v)
+            .map_err(|_| CoreError::InvalidArgument(format!("invalid {label} header value")))?;
+        hmap.insert(name, value);
+    }
+    Ok(hmap)
+}
+
 /// HTTP Basic credentials for an upstream proxy.
 #[derive(Debug, Clone)]
 pub struct ProxyAuth {
@@ -59,6 +222,17 @@ pub struct ProxyAuth {
     pub password: String,
 }
 
+/// Client certificate presented for mutual TLS, both halves PEM-encoded.
+/// PKCS#12 bundles are not accepted: the rustls backend can't read them.
+#[derive(Clone, derive_more::Debug)]
+pub struct ClientIdentity {
+    /// Leaf certificate, optionally followed by its intermediates.
+    pub cert: String,
+    /// Private key (PKCS#8, PKCS#1 or SEC1).
+    #[debug(skip)]
+    pub key: String,
+}
+
 /// Proxy configuration for an [`Agent`].
 #[derive(Debug, Clone, Default)]
 pub enum ProxyConfig {
@@ -75,6 +249,8 @@ pub enum ProxyConfig {
         headers: HashMap<String, String>,
         /// Basic-auth credentials (sent to the proxy, never to the origin).
         auth: Option<ProxyAuth>,
+        /// Destinations that bypass the proxy, in `NO_PROXY` syntax.
+        no_proxy: Vec<String>,
     },
 }
 
@@ -95,10 +271,28 @@ pub struct AgentConfig {
     pub body_timeout: Option<Duration>,
     /// Pool idle timeout.
     pub pool_idle_timeout: Option<Duration>,
+    /// Cap on idle pooled connections per host (`None` = reqwest default,
+    /// unbounded).
+    pub pool_max_idle_per_host: Option<usize>,
+    /// TCP keep-alive probe interval on pooled sockets (`None` = OS default).
+    pub tcp_keepalive: Option<Duration>,
+    /// Disable Nagle's algorithm; defaults to true (reqwest default).
+    pub tcp_nodelay: bool,
     /// 0 = no redirects (undici default).
     pub max_redirections: u32,
     /// When false, force HTTP/1.1 only.
     pub allow_h2: bool,
+    /// Speak HTTP/2 without ALPN/Upgrade negotiation (h2c or pinned h2).
+    /// Requires `allow_h2`.
+    pub http2_prior_knowledge: bool,
+    /// Interval between HTTP/2 PING frames on open connections (`None` =
+    /// no pings, the reqwest default).
+    pub http2_keep_alive_interval: Option<Duration>,
+    /// How long to wait for a PING ack before closing the connection
+    /// (`None` = reqwest default, 20 s). Only used with an interval.
+    pub http2_keep_alive_timeout: Option<Duration>,
+    /// Keep pinging connections with no in-flight streams; off by default.
+    pub http2_keep_alive_while_idle: bool,
     /// Honor Happy-Eyeballs (`auto-select-family`) when set; defaults to true.
     pub auto_select_family: bool,
     /// When false, accept invalid TLS certificates (dangerous).
@@ -107,10 +301,39 @@ pub struct AgentConfig {
     pub reject_invalid_hostnames: bool,
     /// Additional CA certificates in PEM format.
     pub ca: Vec<String>,
+    /// Client certificate for servers that demand mutual TLS.
+    pub identity: Option<ClientIdentity>,
     /// Local address to bind outgoing sockets to.
     pub local_address: Option<IpAddr>,
+    /// Static DNS overrides: host → addresses tried instead of resolving it.
+    /// The port is ignored: reqwest always dials the request URL's port.
+    pub resolve: HashMap<String, Vec<SocketAddr>>,
+    /// Decode `Content-Encoding: gzip` responses. Defaults to on when the
+    /// `gzip` feature is compiled in.
+    pub gzip: bool,
+    /// Decode `Content-Encoding: br` responses (`brotli` feature).
+    pub brotli: bool,
+    /// Decode `Content-Encoding: deflate` responses (`deflate` feature).
+    pub deflate: bool,
+    /// Decode `Content-Encoding: zstd` responses (`zstd` feature).
+    pub zstd: bool,
+    /// `User-Agent` sent when the dispatch doesn't set one (`None` = omit).
+    pub user_agent: Option<String>,
+    /// Headers sent with every request unless the dispatch sets the same name.
+    pub default_headers: HashMap<String, String>,
     /// Response-body byte cap (`None` = uncapped). Enforced in the body loop.
     pub max_response_size: Option<u64>,
+    /// Reject any dispatch or redirect to a non-`https` URL.
+    pub https_only: bool,
+    /// Cap on requests executing at once; extra dispatches wait in FIFO
+    /// order (`None` = unlimited).
+    pub max_concurrent: Option<usize>,
+    /// Cap on dispatches waiting for a `max_concurrent` slot; beyond it they
+    /// fail with [`CoreError::QueueFull`] (`None` = unbounded).
+    pub max_queue: Option<usize>,
+    /// Persist `Set-Cookie` responses and replay them on later dispatches.
+    /// Off by default: undici's Agent is stateless.
+    pub cookies: bool,
     /// Proxy configuration.
     pub proxy: ProxyConfig,
 }
@@ -123,14 +346,33 @@ impl Default for AgentConfig {
             headers_timeout: None,
             body_timeout: None,
             pool_idle_timeout: None,
+            pool_max_idle_per_host: None,
+            tcp_keepalive: None,
+            tcp_nodelay: true,
             max_redirections: 0,
             allow_h2: true,
+            http2_prior_knowledge: false,
+            http2_keep_alive_interval: None,
+            http2_keep_alive_timeout: None,
+            http2_keep_alive_while_idle: false,
             auto_select_family: true,
             reject_unauthorized: true,
             reject_invalid_hostnames: true,
             ca: Vec::new(),
+            identity: None,
             local_address: None,
+            resolve: HashMap::new(),
+            gzip: cfg!(feature = "gzip"),
+            brotli: cfg!(feature = "brotli"),
+            deflate: cfg!(feature = "deflate"),
+            zstd: cfg!(feature = "zstd"),
+            user_agent: None,
+            default_headers: HashMap::new(),
             max_response_size: None,
+            https_only: false,
+            max_concurrent: None,
+            max_queue: None,
+            cookies: false,
             proxy: ProxyConfig::None,
         }
     }
@@ -141,6 +383,44 @@ struct AgentDefaults {
     headers: Option<Duration>,
     body: Option<Duration>,
     max_response_size: Option<u64>,
+    https_only: bool,
+}
+
+/// `maxConcurrent` gate. Waiters are counted separately from the semaphore
+/// so `maxQueue` can reject instead of queueing without bound.
+struct ConcurrencyLimit {
+    permits: Semaphore,
+    max_queue: Option<usize>,
+    queued: AtomicUsize,
+}
+
+impl ConcurrencyLimit {
+    /// Take a slot, waiting in FIFO order if none is free. `Ok(None)` would
+    /// mean the semaphore was closed, which this agent never does.
+    async fn acquire(&self) -> Result<Option<SemaphorePermit<'_>>, CoreError> {
+        if let Ok(permit) = self.permits.try_acquire() {
+            return Ok(Some(permit));
+        }
+        let queued = self.queued.fetch_add(1, Ordering::AcqRel);
+        // Gives the slot back however this future ends, including being
+        // dropped mid-wait when the dispatch is cancelled.
+        let _queued_guard = QueuedGuard(&self.queued);
+        if let Some(max) = self.max_queue
+            && queued >= max
+        {
+            return Err(CoreError::QueueFull(max));
+        }
+        Ok(self.permits.acquire().await.ok())
+    }
+}
+
+/// Decrements `ConcurrencyLimit::queued` on drop.
+struct QueuedGuard<'a>(&'a AtomicUsize);
+
+impl Drop for QueuedGuard<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
 }
 
 struct AgentState {
@@ -152,6 +432,7 @@ struct AgentState {
     destroyed: AtomicBool,
     destroy_error: Mutex<Option<CoreError>>,
     defaults: AgentDefaults,
+    limit: Option<ConcurrencyLimit>,
 }
 
 /// RAII handle for an in-flight dispatch. Holding one keeps `active_count`
@@ -201,7 +482,7 @@ impl Agent {
         reason = "owning AgentConfig at the boundary matches FFI ergonomics"
     )]
     pub fn new(config: AgentConfig) -> Result<Self, CoreError> {
-        let mut builder = Client::builder().cookie_store(false);
+        let mut builder = Client::builder().cookie_store(config.cookies);
 
         if let Some(timeout) = config.timeout {
             builder = builder.timeout(timeout);
@@ -212,6 +493,16 @@ impl Agent {
         if let Some(timeout) = config.pool_idle_timeout {
             builder = builder.pool_idle_timeout(timeout);
         }
+        if let Some(max) = config.pool_max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max);
+        }
+        if let Some(interval) = config.tcp_keepalive {
+            builder = builder.tcp_keepalive(interval);
+        }
+        builder = builder.tcp_nodelay(config.tcp_nodelay);
+        // Also covers redirect hops; the initial URL is checked up front in
+        // `execute_request` for a clearer error.
+        builder = builder.https_only(config.https_only);
 
         builder = builder.redirect(if config.max_redirections == 0 {
             reqwest::redirect::Policy::none()
@@ -219,71 +510,56 @@ impl Agent {
             reqwest::redirect::Policy::limited(config.max_redirections as usize)
         });
 
-        if !config.allow_h2 {
-            builder = builder.http1_only();
-        }
-
-        if !config.reject_unauthorized {
-            builder = builder.danger_accept_invalid_certs(true);
-        }
-        if !config.reject_invalid_hostnames {
-            builder = builder.danger_accept_invalid_hostnames(true);
-        }
+        builder = configure_http2(builder, &config)?;
 
         if let Some(addr) = config.local_address {
             builder = builder.local_address(addr);
         }
 
-        for pem in &config.ca {
-            // `from_pem_bundle` accepts both single PEM certs and multi-cert
-            // bundles (e.g. `/etc/ssl/certs/ca-certificates.crt`); `from_pem`
-            // alone parses just the first block and silently drops the rest,
-            // which broke the mitmproxy CI test that hands us the full system
-            // bundle.
-            let certs = reqwest::Certificate::from_pem_bundle(pem.as_bytes())
-                .map_err(|_| CoreError::InvalidArgument("invalid CA certificate".into()))?;
-            for cert in certs {
-                builder = builder.add_root_certificate(cert);
-            }
+        for (host, addrs) in &config.resolve {
+            builder = builder.resolve_to_addrs(host, addrs);
         }
 
-        match &config.proxy {
-            ProxyConfig::None => {
-                builder = builder.no_proxy();
-            },
-            ProxyConfig::System => {
-                // reqwest with the `system-proxy` feature reads
-                // HTTP_PROXY / HTTPS_PROXY / NO_PROXY automatically.
-            },
-            ProxyConfig::Custom { uri, headers, auth } => {
-                let mut proxy = reqwest::Proxy::all(uri)
-                    .map_err(|e| CoreError::InvalidArgument(format!("invalid proxy URI: {e}")))?;
-                if let Some(auth) = auth {
-                    proxy = proxy.basic_auth(&auth.username, &auth.password);
-                }
-                if !headers.is_empty() {
-                    let mut hmap = reqwest::header::HeaderMap::new();
-                    for (k, v) in headers {
-                        let name = reqwest::header::HeaderName::from_bytes(k.as_bytes()).map_err(
-                            |_| CoreError::InvalidArgument("invalid proxy header name".into()),
-                        )?;
-                        let value = reqwest::header::HeaderValue::from_str(v).map_err(|_| {
-                            CoreError::InvalidArgument("invalid proxy header value".into())
-                        })?;
-                        hmap.insert(name, value);
-                    }
-                    proxy = proxy.headers(hmap);
-                }
-                builder = builder.proxy(proxy);
-            },
+        if let Some(ua) = &config.user_agent {
+            let value = reqwest::header::HeaderValue::from_str(ua)
+                .map_err(|_| CoreError::InvalidArgument("invalid userAgent".into()))?;
+            builder = builder.user_agent(value);
+        }
+
+        // reqwest only fills in a default header when the request doesn't
+        // already carry that name, so per-dispatch headers win.
+        if !config.default_headers.is_empty() {
+            builder = builder.default_headers(header_map(&config.default_headers, "default")?);
         }
 
+        builder = configure_tls(builder, &config)?;
+        builder = configure_proxy(builder, &config.proxy)?;
         builder = configure_happy_eyeballs(builder, config.auto_select_family);
+        builder = configure_decompression(builder, &config)?;
 
         let client = builder
             .build()
             .map_err(|e| CoreError::from_reqwest(e, false))?;
 
+        let limit = match (config.max_concurrent, config.max_queue) {
+            (Some(0), _) => {
+                return Err(CoreError::InvalidArgument(
+                    "maxConcurrent must be at least 1".into(),
+                ));
+            },
+            (Some(max), max_queue) => Some(ConcurrencyLimit {
+                permits: Semaphore::new(max.min(Semaphore::MAX_PERMITS)),
+                max_queue,
+                queued: AtomicUsize::new(0),
+            }),
+            (None, Some(_)) => {
+                return Err(CoreError::InvalidArgument(
+                    "maxQueue requires maxConcurrent".into(),
+                ));
+            },
+            (None, None) => None,
+        };
+
         let state = AgentState {
             next_id: AtomicU64::new(1),
             active_tokens: Mutex::new(HashMap::new()),
@@ -296,7 +572,9 @@ impl Agent {
                 headers: config.headers_timeout,
                 body: config.body_timeout,
                 max_response_size: config.max_response_size,
+                https_only: config.https_only,
             },
+            limit,
         };
 
         Ok(Self {
@@ -349,8 +627,36 @@ impl Agent {
         // forever waiting for a count that no future will ever decrement.
         let active_guard = ActiveRequestGuard::new(Arc::clone(&state), id);
 
+        // The query is left out of the span: it's where tokens tend to live.
+        #[cfg(feature = "tracing")]
+        let span = tracing::info_span!(
+            "dispatch",
+            method = %options.method,
+            url = %format_args!("{}{}", options.origin.as_deref().unwrap_or_default(), options.path),
+            status = tracing::field::Empty,
+        );
+
         let fut: DispatchFuture = Box::pin(async move {
             let _active_guard = active_guard;
+            // Held until the request finishes; queue time doesn't count
+            // against the headers/body timeouts, which start after this.
+            let _permit = match &state.limit {
+                None => None,
+                Some(limit) => select! {
+                    biased;
+                    () = token.cancelled() => {
+                        handler.on_response_error(Self::cancel_reason(&state)).await;
+                        return;
+                    }
+                    acquired = limit.acquire() => match acquired {
+                        Ok(permit) => permit,
+                        Err(e) => {
+                            handler.on_response_error(e).await;
+                            return;
+                        },
+                    },
+                },
+            };
             Self::execute_request(
                 client,
                 options,
@@ -362,6 +668,19 @@ impl Agent {
             .await;
         });
 
+        #[cfg(feature = "tracing")]
+        let fut: DispatchFuture = Box::pin(tracing::Instrument::instrument(
+            async move {
+                let started = std::time::Instant::now();
+                fut.await;
+                tracing::debug!(
+                    elapsed_ms = started.elapsed().as_millis(),
+                    "dispatch finished"
+                );
+            },
+            span,
+        ));
+
         Ok((controller, fut))
     }
 
@@ -375,6 +694,30 @@ impl Agent {
             .unwrap_or(CoreError::RequestAborted)
     }
 
+    /// Parse the target up front so malformed URLs and foreign schemes fail
+    /// with a reason instead of reqwest's opaque builder error.
+    fn request_url(options: &DispatchOptions, https_only: bool) -> Result<Url, CoreError> {
+        let origin = options.origin.as_deref().unwrap_or_default();
+        let raw = if options.query.is_empty() {
+            format!("{}{}", origin, options.path)
+        } else {
+            format!("{}{}?{}", origin, options.path, options.query)
+        };
+        let url = Url::parse(&raw).map_err(|e| {
+            CoreError::InvalidArgument(cap_message_len(&format!("invalid URL {raw:?}: {e}")))
+        })?;
+        match url.scheme() {
+            "https" => Ok(url),
+            "http" if !https_only => Ok(url),
+            "http" => Err(CoreError::InvalidArgument(cap_message_len(&format!(
+                "{origin}: URL scheme violates the httpsOnly policy"
+            )))),
+            scheme => Err(CoreError::InvalidArgument(cap_message_len(&format!(
+                "unsupported URL scheme {scheme:?}: expected http or https"
+            )))),
+        }
+    }
+
     #[expect(
         clippy::too_many_lines,
         reason = "linear request lifecycle is clearer as one body"
@@ -389,14 +732,15 @@ impl Agent {
     ) where
         H: DispatchHandler,
     {
-        let origin = options.origin.as_deref().unwrap_or_default();
-        let url = if options.query.is_empty() {
-            format!("{}{}", origin, options.path)
-        } else {
-            format!("{}{}?{}", origin, options.path, options.query)
+        let url = match Self::request_url(&options, state.defaults.https_only) {
+            Ok(url) => url,
+            Err(e) => {
+                handler.on_response_error(e).await;
+                return;
+            },
         };
 
-        let mut request = client.request(options.method.clone(), &url);
+        let mut request = client.request(options.method.clone(), url);
 
         // Invalid header names/values are deferred by reqwest into the
         // builder's error slot and surface from `.send()` as
@@ -450,8 +794,8 @@ impl Agent {
         // bytes — keeps the header visible to the caller rather than dropping
         // it.
         let headers = response_headers.iter().fold(
-            HashMap::new(),
-            |mut acc: HashMap<String, Vec<String>>, (k, v)| {
+            IndexMap::new(),
+            |mut acc: IndexMap<String, Vec<String>>, (k, v)| {
                 let value = match v.to_str() {
                     Ok(s) => s.to_string(),
                     Err(_) => String::from_utf8_lossy(v.as_bytes()).into_owned(),
@@ -461,6 +805,9 @@ impl Agent {
             },
         );
 
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record("status", response.status().as_u16());
+
         handler
             .on_response_start(ResponseStart {
                 status_code: response.status().as_u16(),
@@ -470,9 +817,17 @@ impl Agent {
                     .unwrap_or_default()
                     .to_string(),
                 headers,
+                remote_addr: response.remote_addr(),
             })
             .await;
 
+        // A HEAD response has no body whatever `Content-Length` says; end
+        // here rather than polling an empty stream under the body timeout.
+        if options.method == reqwest::Method::HEAD {
+            handler.on_response_end(HashMap::new()).await;
+            return;
+        }
+
         let body_timeout_duration = options
             .body_timeout_ms
             .map(Duration::from_millis)
@@ -574,6 +929,22 @@ impl Agent {
             .lock()
             .unwrap_or_else(std::sync::PoisonError::into_inner) = Some(error);
 
+        self.cancel_active();
+        self.wait_for_idle().await;
+    }
+
+    /// Cancel every outstanding request with [`CoreError::RequestAborted`],
+    /// leaving the agent open for new dispatches. Returns how many were
+    /// cancelled; requests that already finished are untouched.
+    #[expect(
+        clippy::must_use_candidate,
+        reason = "the count is informational; callers may ignore it"
+    )]
+    pub fn abort_all(&self) -> usize {
+        self.cancel_active()
+    }
+
+    fn cancel_active(&self) -> usize {
         let tokens: Vec<CancellationToken> = {
             let mut guard = self
                 .state
@@ -582,11 +953,19 @@ impl Agent {
                 .unwrap_or_else(std::sync::PoisonError::into_inner);
             guard.drain().map(|(_id, t)| t).collect()
         };
-        for token in tokens {
+        for token in &tokens {
             token.cancel();
         }
+        tokens.len()
+    }
 
-        self.wait_for_idle().await;
+    /// Dispatches accepted and not yet finished: counted from
+    /// [`Agent::dispatch`] until its future completes or is dropped, so
+    /// requests still waiting for a `max_concurrent` slot are included.
+    /// Exact, but a snapshot — it can change as soon as it's read.
+    #[must_use]
+    pub fn in_flight(&self) -> usize {
+        self.state.active_count.load(Ordering::Acquire)
     }
 
     /// Whether `close()` has been called.
@@ -627,6 +1006,155 @@ mod tests {
         assert!(agent.is_ok(), "explicit timeouts must construct");
     }
 
+    #[test]
+    fn agent_creation_with_pool_and_socket_tuning() {
+        let config = AgentConfig {
+            pool_max_idle_per_host: Some(0),
+            tcp_keepalive: Some(Duration::from_secs(15)),
+            tcp_nodelay: false,
+            ..Default::default()
+        };
+        let agent = Agent::new(config);
+        assert!(agent.is_ok(), "pool and socket tuning must construct");
+    }
+
+    #[test]
+    fn agent_creation_with_http2_keep_alive() {
+        let config = AgentConfig {
+            http2_keep_alive_interval: Some(Duration::from_secs(30)),
+            http2_keep_alive_timeout: Some(Duration::from_secs(5)),
+            http2_keep_alive_while_idle: true,
+            ..Default::default()
+        };
+        assert!(
+            Agent::new(config).is_ok(),
+            "HTTP/2 keep-alive must construct"
+        );
+    }
+
+    #[test]
+    fn agent_creation_rejects_prior_knowledge_without_h2() {
+        let config = AgentConfig {
+            allow_h2: false,
+            http2_prior_knowledge: true,
+            ..Default::default()
+        };
+        assert!(
+            matches!(Agent::new(config), Err(CoreError::InvalidArgument(_))),
+            "prior knowledge with HTTP/1.1-only must be rejected"
+        );
+    }
+
+    #[test]
+    fn agent_creation_rejects_invalid_default_header() {
+        let config = AgentConfig {
+            default_headers: HashMap::from([("bad name".into(), "v".into())]),
+            ..Default::default()
+        };
+        assert!(
+            matches!(Agent::new(config), Err(CoreError::InvalidArgument(msg)) if msg.contains("default")),
+            "invalid default header name must be rejected"
+        );
+    }
+
+    #[test]
+    fn agent_creation_rejects_malformed_identity() {
+        let config = AgentConfig {
+            identity: Some(ClientIdentity {
+                cert: "not a certificate".into(),
+                key: "not a key".into(),
+            }),
+            ..Default::default()
+        };
+        assert!(
+            matches!(Agent::new(config), Err(CoreError::InvalidArgument(msg)) if msg.contains("client identity")),
+            "malformed client identity must be rejected"
+        );
+    }
+
+    #[test]
+    fn request_url_reports_parse_and_scheme_errors() {
+        let target = |origin: &str| DispatchOptions {
+            origin: Some(origin.into()),
+            path: "/a".into(),
+            ..Default::default()
+        };
+        let err = |origin: &str, https_only: bool| match Agent::request_url(
+            &target(origin),
+            https_only,
+        ) {
+            Err(CoreError::InvalidArgument(msg)) => msg,
+            other => format!("unexpected: {other:?}"),
+        };
+        assert!(
+            err("http://[::1", false).starts_with("invalid URL"),
+            "parse reason"
+        );
+        assert!(
+            err("ftp://example.com", false).contains("\"ftp\""),
+            "names the scheme"
+        );
+        assert!(
+            err("http://example.com", true).contains("httpsOnly"),
+            "https-only"
+        );
+        assert!(
+            Agent::request_url(&target("https://example.com"), true)
+                .is_ok_and(|url| url.as_str() == "https://example.com/a"),
+            "valid URL passes"
+        );
+    }
+
+    #[test]
+    fn agent_creation_rejects_codecs_not_compiled_in() {
+        let codecs = [
+            (
+                "gzip",
+                cfg!(feature = "gzip"),
+                AgentConfig {
+                    gzip: true,
+                    ..Default::default()
+                },
+            ),
+            (
+                "brotli",
+                cfg!(feature = "brotli"),
+                AgentConfig {
+                    brotli: true,
+                    ..Default::default()
+                },
+            ),
+            (
+                "deflate",
+                cfg!(feature = "deflate"),
+                AgentConfig {
+                    deflate: true,
+                    ..Default::default()
+                },
+            ),
+            (
+                "zstd",
+                cfg!(feature = "zstd"),
+                AgentConfig {
+                    zstd: true,
+                    ..Default::default()
+                },
+            ),
+        ];
+        for (name, compiled, config) in codecs {
+            let result = Agent::new(config);
+            if compiled {
+                assert!(result.is_ok(), "{name} is compiled in");
+            } else {
+                assert!(
+                    matches!(&result, Err(CoreError::InvalidArgument(msg)) if msg.starts_with(name) && msg.contains("not compiled in")),
+                    "{name}: expected not-compiled-in error, got {:?}",
+                    result.err()
+                );
+            }
+        }
+    }
+
     #[test]
     fn agent_lifecycle_states() -> Result<()> {
         let agent = Agent::new(AgentConfig::default()).context("agent")?;
@@ -8,13 +8,16 @@
 
 use std::collections::HashMap;
 use std::future::Future;
+use std::net::SocketAddr;
 use std::sync::Arc;
 
 use bytes::Bytes;
+use indexmap::IndexMap;
 use tokio::sync::watch;
 use tokio_util::sync::CancellationToken;
 
 use crate::error::CoreError;
+use crate::error::cap_message_len;
 
 /// Per-dispatch header cap (request and response). Bounds marshalling time
 /// across the FFI boundary; the FFI layer enforces it on the request side.
@@ -27,11 +30,13 @@ pub use reqwest::Method;
 
 /// Parse a method name (case-insensitive). RFC 7230 token validation is
 /// delegated to `http::Method::from_bytes`; this layer adds the
-/// dispatcher's policy (CONNECT/TRACE → `NotSupported`).
+/// dispatcher's policy (CONNECT/TRACE → `NotSupported`). The rejected name
+/// is echoed `Debug`-quoted (control bytes escaped) and length-capped.
 pub fn parse_method(name: &str) -> Result<Method, CoreError> {
     let upper = name.to_ascii_uppercase();
-    let m = Method::from_bytes(upper.as_bytes())
-        .map_err(|_| CoreError::InvalidArgument("invalid HTTP method".into()))?;
+    let m = Method::from_bytes(upper.as_bytes()).map_err(|_| {
+        CoreError::InvalidArgument(cap_message_len(&format!("invalid HTTP method: {name:?}")))
+    })?;
     if m == Method::CONNECT || m == Method::TRACE {
         return Err(CoreError::NotSupported(
             "CONNECT/TRACE not supported".into(),
@@ -76,12 +81,16 @@ impl Default for DispatchOptions {
 
 /// Response-start metadata. `status_message` is the IANA canonical reason
 /// phrase (server-supplied phrases are discarded to block reason-phrase
-/// smuggling).
+/// smuggling). `headers` keeps names in wire order (first occurrence) and
+/// every value of a repeated name, so callers can rebuild Node-style
+/// `rawHeaders`. `remote_addr` is the peer the response arrived from, when
+/// the connector knows it.
 #[derive(Debug, Clone)]
 pub struct ResponseStart {
     pub status_code: u16,
     pub status_message: String,
-    pub headers: HashMap<String, Vec<String>>,
+    pub headers: IndexMap<String, Vec<String>>,
+    pub remote_addr: Option<SocketAddr>,
 }
 
 /// Sink for dispatch lifecycle events. See the module doc for the
@@ -195,6 +204,37 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn parse_method_is_case_insensitive() -> Result<()> {
+        ensure!(parse_method("get")? == Method::GET, "lowercase get");
+        ensure!(parse_method("Post")? == Method::POST, "mixed-case post");
+        ensure!(parse_method("PATCH")? == Method::PATCH, "uppercase patch");
+        ensure!(
+            parse_method("PROPFIND")?.as_str() == "PROPFIND",
+            "extension methods pass through"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn parse_method_rejects_invalid_tokens() {
+        let err = parse_method("GE T").err();
+        assert!(
+            matches!(&err, Some(CoreError::InvalidArgument(msg)) if msg.contains("\"GE T\"")),
+            "invalid token must name the offending value: {err:?}"
+        );
+    }
+
+    #[test]
+    fn parse_method_rejects_connect_and_trace() {
+        for name in ["CONNECT", "trace"] {
+            assert!(
+                matches!(parse_method(name), Err(CoreError::NotSupported(_))),
+                "{name} must be NotSupported"
+            );
+        }
+    }
+
     #[test]
     fn pause_state_atomic_operations() {
         let state = PauseState::new();
@@ -80,6 +80,10 @@ pub enum CoreError {
     #[error("Redirect error: {0}")]
     Redirect(String),
 
+    /// `maxQueue` requests are already waiting for a `maxConcurrent` slot.
+    #[error("Request queue is full (max {0} waiting)")]
+    QueueFull(usize),
+
     /// Surfaced for 4xx/5xx responses when `throwOnError` is enabled; carries
     /// the body and headers captured before the failure (both may be empty).
     #[error("{message}")]
@@ -106,6 +110,7 @@ impl CoreError {
             Self::ClientClosed => "UND_ERR_CLOSED",
             Self::NotSupported(_) => "UND_ERR_NOT_SUPPORTED",
             Self::Redirect(_) => "UND_ERR_REDIRECT",
+            Self::QueueFull(_) => "UND_ERR_QUEUE_FULL",
             Self::ResponseError { .. } => "UND_ERR_RESPONSE",
         }
     }
@@ -139,8 +144,13 @@ impl CoreError {
             };
         }
 
+        // reqwest's `Display` for redirect errors stops at "error following
+        // redirect"; the reason (e.g. "too many redirects") lives in the source.
         if err.is_redirect() {
-            return Self::Redirect(cap_message_len(&err.to_string()));
+            return Self::Redirect(cap_message_len(&format!(
+                "{err}; source: {}",
+                error_chain(&err)
+            )));
         }
 
         if err.is_connect() {
@@ -198,6 +208,11 @@ mod tests {
             "UND_ERR_REDIRECT",
             "redirect"
         );
+        assert_eq!(
+            CoreError::QueueFull(0).error_code(),
+            "UND_ERR_QUEUE_FULL",
+            "queue full"
+        );
     }
 
     #[test]
@@ -265,4 +280,40 @@ mod tests {
         );
         Ok(())
     }
+
+    #[tokio::test]
+    async fn from_reqwest_redirect_carries_reason() -> Result<()> {
+        use wiremock::Mock;
+        use wiremock::MockServer;
+        use wiremock::ResponseTemplate;
+        use wiremock::matchers::path;
+
+        let server = MockServer::start().await;
+        Mock::given(path("/loop"))
+            .respond_with(ResponseTemplate::new(302).insert_header("location", "/loop"))
+            .mount(&server)
+            .await;
+
+        let client = reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::limited(1))
+            .build()
+            .context("client build")?;
+        let err = client
+            .get(format!("{}/loop", server.uri()))
+            .send()
+            .await
+            .err()
+            .context("expected redirect failure")?;
+        let mapped = CoreError::from_reqwest(err, false);
+        ensure!(
+            mapped.error_code() == "UND_ERR_REDIRECT",
+            "unexpected classification: {}",
+            mapped.error_code()
+        );
+        ensure!(
+            mapped.to_string().contains("too many redirects"),
+            "message must carry the reason: {mapped}"
+        );
+        Ok(())
+    }
 }
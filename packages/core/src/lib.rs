@@ -8,6 +8,7 @@ pub mod error;
 
 pub use agent::Agent;
 pub use agent::AgentConfig;
+pub use agent::ClientIdentity;
 pub use agent::DispatchFuture;
 pub use agent::DispatchHandle;
 pub use agent::ProxyAuth;
@@ -4,20 +4,72 @@
 
 mod support;
 
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::time::Duration;
+
 use anyhow::Context;
 use anyhow::Result;
 use anyhow::ensure;
+use bytes::Bytes;
 use nrcore::Agent;
 use nrcore::AgentConfig;
 use nrcore::DispatchOptions;
 use nrcore::Method;
+use nrcore::ProxyConfig;
 use support::mock_handler::MockHandler;
+use support::mock_handler::RecordedEvents;
 use wiremock::Mock;
 use wiremock::MockServer;
 use wiremock::ResponseTemplate;
+use wiremock::matchers::body_string;
+use wiremock::matchers::header;
 use wiremock::matchers::method;
 use wiremock::matchers::path;
 
+/// `GET {origin}{path}` carrying `headers`.
+fn get(origin: String, path: &str, headers: HashMap<String, Vec<String>>) -> DispatchOptions {
+    DispatchOptions {
+        origin: Some(origin),
+        path: path.to_string(),
+        method: Method::GET,
+        headers,
+        ..Default::default()
+    }
+}
+
+/// Drive one dispatch to its terminal callback and hand back what the
+/// handler recorded.
+async fn run(agent: &Agent, opts: DispatchOptions) -> Result<RecordedEvents> {
+    let (handler, events, done) = MockHandler::new();
+    let (_ctrl, fut) = agent.dispatch(opts, handler).context("dispatch")?;
+    tokio::spawn(fut);
+    done.notified().await;
+    Ok(std::mem::take(&mut *events.lock().await))
+}
+
+async fn dispatch_get(
+    agent: &Agent,
+    origin: String,
+    path: &str,
+    headers: HashMap<String, Vec<String>>,
+) -> Result<RecordedEvents> {
+    run(agent, get(origin, path, headers)).await
+}
+
+/// Status of the single response, or the transport errors if none arrived.
+fn status(events: &RecordedEvents) -> Result<u16> {
+    events
+        .response_starts
+        .first()
+        .map(|start| start.status_code)
+        .with_context(|| format!("no response, errors: {:?}", events.errors))
+}
+
+fn body(events: &RecordedEvents) -> Vec<u8> {
+    events.data_chunks.iter().flatten().copied().collect()
+}
+
 #[tokio::test]
 async fn test_get_200_ok() -> Result<()> {
     let server = MockServer::start().await;
@@ -49,3 +101,693 @@ async fn test_get_200_ok() -> Result<()> {
     ensure!(events.errors.is_empty(), "no errors");
     Ok(())
 }
+
+#[tokio::test]
+async fn test_response_headers_keep_wire_order_and_duplicates() -> Result<()> {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/multi"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .append_header("x-first", "1")
+                .append_header("set-cookie", "a=1")
+                .append_header("set-cookie", "b=2")
+                .append_header("x-last", "2"),
+        )
+        .mount(&server)
+        .await;
+
+    let agent = Agent::new(AgentConfig::default()).context("agent")?;
+    let events = dispatch_get(&agent, server.uri(), "/multi", HashMap::new()).await?;
+    ensure!(status(&events)? == 200, "status 200");
+    let headers = &events.response_starts[0].headers;
+    let names: Vec<&str> = headers
+        .keys()
+        .map(String::as_str)
+        .filter(|name| ["x-first", "set-cookie", "x-last"].contains(name))
+        .collect();
+    ensure!(
+        names == ["x-first", "set-cookie", "x-last"],
+        "wire order: {names:?}"
+    );
+    let cookies = headers.get("set-cookie").cloned().unwrap_or_default();
+    ensure!(cookies == ["a=1", "b=2"], "set-cookie values: {cookies:?}");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_head_reports_content_length_without_body() -> Result<()> {
+    let server = MockServer::start().await;
+    Mock::given(method("HEAD"))
+        .and(path("/head"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("hello"))
+        .mount(&server)
+        .await;
+
+    let agent = Agent::new(AgentConfig::default()).context("agent")?;
+    let opts = DispatchOptions {
+        method: Method::HEAD,
+        ..get(server.uri(), "/head", HashMap::new())
+    };
+    let events = run(&agent, opts).await?;
+    ensure!(status(&events)? == 200, "status 200");
+    let length = events.response_starts[0]
+        .headers
+        .get("content-length")
+        .cloned();
+    ensure!(
+        length == Some(vec!["5".to_string()]),
+        "content-length {length:?}"
+    );
+    ensure!(events.data_chunks.is_empty(), "no body chunks");
+    ensure!(events.response_ends.len() == 1, "one response end");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_in_flight_tracks_dispatch_lifetime() -> Result<()> {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+
+    let agent = Agent::new(AgentConfig::default()).context("agent")?;
+    ensure!(agent.in_flight() == 0, "fresh agent has nothing in flight");
+    let (handler, _events, _done) = MockHandler::new();
+    let (_ctrl, fut) = agent
+        .dispatch(get(server.uri(), "/", HashMap::new()), handler)
+        .context("dispatch")?;
+    ensure!(agent.in_flight() == 1, "counted from dispatch()");
+    tokio::spawn(fut).await.context("join")?;
+    ensure!(agent.in_flight() == 0, "released once the future completes");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_max_concurrent_queues_then_rejects_overflow() -> Result<()> {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_millis(200)))
+        .mount(&server)
+        .await;
+
+    let agent = Agent::new(AgentConfig {
+        max_concurrent: Some(1),
+        max_queue: Some(1),
+        ..Default::default()
+    })
+    .context("agent")?;
+
+    // First takes the only slot, second waits for it, third overflows.
+    let mut runs = Vec::new();
+    for _ in 0..3 {
+        let (handler, events, _done) = MockHandler::new();
+        let (_ctrl, fut) = agent
+            .dispatch(get(server.uri(), "/", HashMap::new()), handler)
+            .context("dispatch")?;
+        runs.push((tokio::spawn(fut), events));
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    for (task, _) in &mut runs {
+        task.await.context("join")?;
+    }
+
+    for (i, (_, events)) in runs.iter().enumerate().take(2) {
+        let events = events.lock().await;
+        ensure!(
+            events.errors.is_empty(),
+            "request {i} errors: {:?}",
+            events.errors
+        );
+        ensure!(events.response_ends.len() == 1, "request {i} completes");
+    }
+    let overflow = runs[2].1.lock().await;
+    ensure!(overflow.response_starts.is_empty(), "overflow never sent");
+    ensure!(
+        overflow.errors.len() == 1 && overflow.errors[0].contains("queue is full"),
+        "overflow rejected: {:?}",
+        overflow.errors
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_max_concurrent_aborted_waiter_frees_queue_slot() -> Result<()> {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_millis(200)))
+        .mount(&server)
+        .await;
+
+    let agent = Agent::new(AgentConfig {
+        max_concurrent: Some(1),
+        max_queue: Some(1),
+        ..Default::default()
+    })
+    .context("agent")?;
+
+    let (handler, _events, _done) = MockHandler::new();
+    let (_ctrl, fut) = agent
+        .dispatch(get(server.uri(), "/", HashMap::new()), handler)
+        .context("dispatch")?;
+    let holder = tokio::spawn(fut);
+    tokio::time::sleep(Duration::from_millis(20)).await;
+
+    // Queue one behind the holder, then abort it while it waits.
+    let (handler, aborted, _done) = MockHandler::new();
+    let (ctrl, fut) = agent
+        .dispatch(get(server.uri(), "/", HashMap::new()), handler)
+        .context("dispatch")?;
+    let waiter = tokio::spawn(fut);
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    ctrl.abort();
+    waiter.await.context("join")?;
+    ensure!(
+        aborted.lock().await.response_starts.is_empty(),
+        "aborted waiter never sent"
+    );
+
+    let (handler, events, _done) = MockHandler::new();
+    let (_ctrl, fut) = agent
+        .dispatch(get(server.uri(), "/", HashMap::new()), handler)
+        .context("dispatch")?;
+    tokio::spawn(fut).await.context("join")?;
+    holder.await.context("join")?;
+    let events = events.lock().await;
+    ensure!(
+        events.errors.is_empty(),
+        "queue slot leaked: {:?}",
+        events.errors
+    );
+    ensure!(events.response_ends.len() == 1, "later request completes");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_response_start_reports_remote_addr() -> Result<()> {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+
+    let agent = Agent::new(AgentConfig::default()).context("agent")?;
+    let events = dispatch_get(&agent, server.uri(), "/", HashMap::new()).await?;
+    let remote_addr = events
+        .response_starts
+        .first()
+        .and_then(|start| start.remote_addr);
+    ensure!(
+        remote_addr == Some(*server.address()),
+        "remote addr {remote_addr:?} is the mock server"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_http2_prior_knowledge_over_cleartext() -> Result<()> {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/h2c"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("h2"))
+        .mount(&server)
+        .await;
+
+    let agent = Agent::new(AgentConfig {
+        http2_prior_knowledge: true,
+        ..Default::default()
+    })
+    .context("agent")?;
+    let events = dispatch_get(&agent, server.uri(), "/h2c", HashMap::new()).await?;
+    ensure!(status(&events)? == 200, "status 200");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_default_headers_apply_to_requests() -> Result<()> {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/inherit"))
+        .and(header("x-team", "core"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+
+    let agent = Agent::new(AgentConfig {
+        default_headers: HashMap::from([("x-team".into(), "core".into())]),
+        ..Default::default()
+    })
+    .context("agent")?;
+    let events = dispatch_get(&agent, server.uri(), "/inherit", HashMap::new()).await?;
+    let code = status(&events)?;
+    ensure!(code == 200, "default header sent: status {code}");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_request_headers_override_default_headers() -> Result<()> {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/override"))
+        .and(header("x-team", "node"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+
+    let agent = Agent::new(AgentConfig {
+        default_headers: HashMap::from([("x-team".into(), "core".into())]),
+        ..Default::default()
+    })
+    .context("agent")?;
+    let headers = HashMap::from([("x-team".to_string(), vec!["node".to_string()])]);
+    let events = dispatch_get(&agent, server.uri(), "/override", headers).await?;
+    let code = status(&events)?;
+    ensure!(code == 200, "request header wins: status {code}");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_user_agent_option_sets_header() -> Result<()> {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/default"))
+        .and(header("user-agent", "node_reqwest/test"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+
+    let agent = Agent::new(AgentConfig {
+        user_agent: Some("node_reqwest/test".into()),
+        ..Default::default()
+    })
+    .context("agent")?;
+    let events = dispatch_get(&agent, server.uri(), "/default", HashMap::new()).await?;
+    let code = status(&events)?;
+    ensure!(code == 200, "agent user-agent sent: status {code}");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_request_user_agent_overrides_option() -> Result<()> {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/custom"))
+        .and(header("user-agent", "custom/1.0"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+
+    let agent = Agent::new(AgentConfig {
+        user_agent: Some("node_reqwest/test".into()),
+        ..Default::default()
+    })
+    .context("agent")?;
+    let headers = HashMap::from([("user-agent".to_string(), vec!["custom/1.0".to_string()])]);
+    let events = dispatch_get(&agent, server.uri(), "/custom", headers).await?;
+    let code = status(&events)?;
+    ensure!(code == 200, "request user-agent wins: status {code}");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_explicit_host_header_overrides_authority() -> Result<()> {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/vhost"))
+        .and(header("host", "tenant.example"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+
+    let agent = Agent::new(AgentConfig::default()).context("agent")?;
+    let headers = HashMap::from([("host".to_string(), vec!["tenant.example".to_string()])]);
+    let events = dispatch_get(&agent, server.uri(), "/vhost", headers).await?;
+    let code = status(&events)?;
+    ensure!(code == 200, "vhost routed: status {code}");
+    Ok(())
+}
+
+/// `gzip(b"hello")`, fixed mtime so the bytes are stable.
+#[cfg(feature = "gzip")]
+const GZIP_HELLO: &[u8] = &[
+    0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x03, 0xcb, 0x48, 0xcd, 0xc9, 0xc9, 0x07,
+    0x00, 0x86, 0xa6, 0x10, 0x36, 0x05, 0x00, 0x00, 0x00,
+];
+
+/// Serve [`GZIP_HELLO`] labelled `content-encoding: gzip` at `/gz`.
+#[cfg(feature = "gzip")]
+async fn gzip_server() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/gz"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-encoding", "gzip")
+                .set_body_bytes(GZIP_HELLO),
+        )
+        .mount(&server)
+        .await;
+    server
+}
+
+#[cfg(feature = "gzip")]
+#[tokio::test]
+async fn test_gzip_enabled_decodes_body() -> Result<()> {
+    let server = gzip_server().await;
+    let agent = Agent::new(AgentConfig {
+        gzip: true,
+        ..Default::default()
+    })
+    .context("agent")?;
+    let events = dispatch_get(&agent, server.uri(), "/gz", HashMap::new()).await?;
+    ensure!(status(&events)? == 200, "status 200");
+    let body = body(&events);
+    ensure!(body == b"hello", "decoded body {body:?}");
+    let encoding = events.response_starts[0].headers.get("content-encoding");
+    ensure!(
+        encoding.is_none(),
+        "content-encoding stripped: {encoding:?}"
+    );
+    Ok(())
+}
+
+#[cfg(feature = "gzip")]
+#[tokio::test]
+async fn test_gzip_disabled_passes_body_through() -> Result<()> {
+    let server = gzip_server().await;
+    let agent = Agent::new(AgentConfig {
+        gzip: false,
+        ..Default::default()
+    })
+    .context("agent")?;
+    let events = dispatch_get(&agent, server.uri(), "/gz", HashMap::new()).await?;
+    ensure!(status(&events)? == 200, "status 200");
+    let body = body(&events);
+    ensure!(body == GZIP_HELLO, "raw body {body:?}");
+    let encoding = events.response_starts[0]
+        .headers
+        .get("content-encoding")
+        .cloned();
+    ensure!(
+        encoding == Some(vec!["gzip".to_string()]),
+        "content-encoding kept: {encoding:?}"
+    );
+    Ok(())
+}
+
+/// A single raw-block zstd frame holding `b"hello"`.
+#[cfg(feature = "zstd")]
+const ZSTD_HELLO: &[u8] = &[
+    0x28, 0xb5, 0x2f, 0xfd, 0x20, 0x05, 0x29, 0x00, 0x00, 0x68, 0x65, 0x6c, 0x6c, 0x6f,
+];
+
+/// Serve [`ZSTD_HELLO`] labelled `content-encoding: zstd` at `/zst`.
+#[cfg(feature = "zstd")]
+async fn zstd_server() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/zst"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-encoding", "zstd")
+                .set_body_bytes(ZSTD_HELLO),
+        )
+        .mount(&server)
+        .await;
+    server
+}
+
+#[cfg(feature = "zstd")]
+#[tokio::test]
+async fn test_zstd_enabled_decodes_body() -> Result<()> {
+    let server = zstd_server().await;
+    let agent = Agent::new(AgentConfig {
+        zstd: true,
+        ..Default::default()
+    })
+    .context("agent")?;
+    let events = dispatch_get(&agent, server.uri(), "/zst", HashMap::new()).await?;
+    ensure!(status(&events)? == 200, "status 200");
+    let body = body(&events);
+    ensure!(body == b"hello", "decoded body {body:?}");
+    Ok(())
+}
+
+#[cfg(feature = "zstd")]
+#[tokio::test]
+async fn test_zstd_disabled_passes_body_through() -> Result<()> {
+    let server = zstd_server().await;
+    let agent = Agent::new(AgentConfig {
+        zstd: false,
+        ..Default::default()
+    })
+    .context("agent")?;
+    let events = dispatch_get(&agent, server.uri(), "/zst", HashMap::new()).await?;
+    ensure!(status(&events)? == 200, "status 200");
+    let body = body(&events);
+    ensure!(body == ZSTD_HELLO, "raw body {body:?}");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_resolve_override_pins_host() -> Result<()> {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/pinned"))
+        .and(header(
+            "host",
+            format!("staging.invalid:{}", server.address().port()),
+        ))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+
+    // The pinned port is ignored; reqwest dials the URL's port.
+    let pinned = SocketAddr::new(server.address().ip(), 0);
+    let agent = Agent::new(AgentConfig {
+        resolve: HashMap::from([("staging.invalid".to_string(), vec![pinned])]),
+        ..Default::default()
+    })
+    .context("agent")?;
+
+    let origin = format!("http://staging.invalid:{}", server.address().port());
+    let events = dispatch_get(&agent, origin, "/pinned", HashMap::new()).await?;
+    let code = status(&events)?;
+    ensure!(code == 200, "status {code}");
+    Ok(())
+}
+
+/// A proxy answering `via proxy` and an origin answering `direct` at `/direct`.
+async fn proxy_and_origin() -> (MockServer, MockServer) {
+    let proxy = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("via proxy"))
+        .mount(&proxy)
+        .await;
+    let origin = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/direct"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("direct"))
+        .mount(&origin)
+        .await;
+    (proxy, origin)
+}
+
+fn custom_proxy(proxy: &MockServer, no_proxy: Vec<String>) -> AgentConfig {
+    AgentConfig {
+        proxy: ProxyConfig::Custom {
+            uri: proxy.uri(),
+            headers: HashMap::new(),
+            auth: None,
+            no_proxy,
+        },
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_custom_proxy_carries_requests() -> Result<()> {
+    let (proxy, origin) = proxy_and_origin().await;
+    let agent = Agent::new(custom_proxy(&proxy, Vec::new())).context("agent")?;
+    let events = dispatch_get(&agent, origin.uri(), "/direct", HashMap::new()).await?;
+    ensure!(status(&events)? == 200, "status 200");
+    let body = body(&events);
+    ensure!(body == b"via proxy", "body {body:?}");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_no_proxy_bypasses_custom_proxy() -> Result<()> {
+    let (proxy, origin) = proxy_and_origin().await;
+    let agent = Agent::new(custom_proxy(&proxy, vec!["127.0.0.1".to_string()])).context("agent")?;
+    let events = dispatch_get(&agent, origin.uri(), "/direct", HashMap::new()).await?;
+    ensure!(status(&events)? == 200, "status 200");
+    let body = body(&events);
+    ensure!(body == b"direct", "body {body:?}");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_https_only_rejects_cleartext() -> Result<()> {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    let agent = Agent::new(AgentConfig {
+        https_only: true,
+        ..Default::default()
+    })
+    .context("agent")?;
+
+    let events = dispatch_get(&agent, server.uri(), "/", HashMap::new()).await?;
+    ensure!(
+        events.response_starts.is_empty(),
+        "cleartext request must not be sent"
+    );
+    ensure!(
+        events.errors.len() == 1 && events.errors[0].contains("httpsOnly"),
+        "errors: {:?}",
+        events.errors
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_cookie_jar_replays_set_cookie() -> Result<()> {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/login"))
+        .respond_with(ResponseTemplate::new(200).insert_header("set-cookie", "session=abc; Path=/"))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/me"))
+        .and(header("cookie", "session=abc"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("known"))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/me"))
+        .respond_with(ResponseTemplate::new(401))
+        .mount(&server)
+        .await;
+
+    let agent = Agent::new(AgentConfig {
+        cookies: true,
+        ..Default::default()
+    })
+    .context("agent")?;
+
+    let login = dispatch_get(&agent, server.uri(), "/login", HashMap::new()).await?;
+    ensure!(status(&login)? == 200, "login status 200");
+    let me = dispatch_get(&agent, server.uri(), "/me", HashMap::new()).await?;
+    let code = status(&me)?;
+    ensure!(code == 200, "cookie replayed: status {code}");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_cookie_jar_disabled_by_default() -> Result<()> {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/login"))
+        .respond_with(ResponseTemplate::new(200).insert_header("set-cookie", "session=abc; Path=/"))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/me"))
+        .and(header("cookie", "session=abc"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/me"))
+        .respond_with(ResponseTemplate::new(401))
+        .mount(&server)
+        .await;
+
+    let agent = Agent::new(AgentConfig::default()).context("agent")?;
+
+    let login = dispatch_get(&agent, server.uri(), "/login", HashMap::new()).await?;
+    ensure!(status(&login)? == 200, "login status 200");
+    let me = dispatch_get(&agent, server.uri(), "/me", HashMap::new()).await?;
+    let code = status(&me)?;
+    ensure!(code == 401, "cookie not replayed: status {code}");
+    Ok(())
+}
+
+/// `POST {origin}{path}` streaming `hello` in two chunks, so reqwest can't
+/// know the length up front.
+fn post_streamed(
+    origin: String,
+    path: &str,
+    headers: HashMap<String, Vec<String>>,
+) -> DispatchOptions {
+    let chunks = ["he", "llo"].map(|c| Ok::<_, std::io::Error>(Bytes::from_static(c.as_bytes())));
+    DispatchOptions {
+        method: Method::POST,
+        body: Some(reqwest::Body::wrap_stream(futures::stream::iter(chunks))),
+        ..get(origin, path, headers)
+    }
+}
+
+#[tokio::test]
+async fn test_streamed_body_without_length_is_chunked() -> Result<()> {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chunked"))
+        .and(header("transfer-encoding", "chunked"))
+        .and(body_string("hello"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+
+    let agent = Agent::new(AgentConfig::default()).context("agent")?;
+    let events = run(
+        &agent,
+        post_streamed(server.uri(), "/chunked", HashMap::new()),
+    )
+    .await?;
+    let code = status(&events)?;
+    ensure!(code == 200, "chunked framing not matched: status {code}");
+    let requests = server.received_requests().await.unwrap_or_default();
+    ensure!(
+        requests
+            .iter()
+            .all(|r| !r.headers.contains_key("content-length")),
+        "no content-length alongside chunked"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_streamed_body_with_content_length_is_sized() -> Result<()> {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/sized"))
+        .and(header("content-length", "5"))
+        .and(body_string("hello"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+
+    let agent = Agent::new(AgentConfig::default()).context("agent")?;
+    // A caller-supplied `content-length` switches hyper to fixed-length
+    // framing for servers that refuse chunked.
+    let headers = HashMap::from([("content-length".to_string(), vec!["5".to_string()])]);
+    let events = run(&agent, post_streamed(server.uri(), "/sized", headers)).await?;
+    let code = status(&events)?;
+    ensure!(code == 200, "sized framing not matched: status {code}");
+    let requests = server.received_requests().await.unwrap_or_default();
+    ensure!(
+        requests
+            .iter()
+            .all(|r| !r.headers.contains_key("transfer-encoding")),
+        "no transfer-encoding alongside content-length"
+    );
+    Ok(())
+}
@@ -266,6 +266,77 @@ async fn test_destroy_cancels_pending() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_abort_all_cancels_pending_and_keeps_agent_open() -> Result<()> {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/slow"))
+        .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_mins(1)))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/fast"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+
+    let agent = Agent::new(AgentConfig::default()).context("agent")?;
+
+    let (fast, fast_events, _) = MockHandler::new();
+    let (_ctrl, fut) = agent
+        .dispatch(opts(server.uri(), "/fast"), fast)
+        .context("dispatch fast")?;
+    tokio::spawn(fut).await.context("join fast")?;
+
+    let mut slow = Vec::new();
+    for _ in 0..2 {
+        let (handler, events, _) = MockHandler::new();
+        let (_ctrl, fut) = agent
+            .dispatch(opts(server.uri(), "/slow"), handler)
+            .context("dispatch slow")?;
+        slow.push((tokio::spawn(fut), events));
+    }
+    // Wait until both slow requests are on the wire (the fast one is the
+    // first recorded) so the abort lands mid-flight, not before connect.
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while server.received_requests().await.map_or(0, |r| r.len()) < 3 {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+    })
+    .await
+    .context("slow requests never reached the server")?;
+    ensure!(agent.abort_all() == 2, "both slow requests cancelled");
+
+    for (task, events) in slow {
+        task.await.context("join slow")?;
+        let events = events.lock().await;
+        ensure!(
+            events.errors.len() == 1 && events.errors[0].contains("aborted"),
+            "aborted: {:?}",
+            events.errors
+        );
+    }
+    let fast_events = fast_events.lock().await;
+    ensure!(
+        fast_events.errors.is_empty() && fast_events.response_ends.len() == 1,
+        "completed request unaffected"
+    );
+    drop(fast_events);
+
+    let (after, after_events, _) = MockHandler::new();
+    let (_ctrl, fut) = agent
+        .dispatch(opts(server.uri(), "/fast"), after)
+        .context("dispatch after abort_all")?;
+    tokio::spawn(fut).await.context("join after")?;
+    let after_events = after_events.lock().await;
+    ensure!(
+        after_events.errors.is_empty() && after_events.response_ends.len() == 1,
+        "agent still serves requests after abort_all: {:?}",
+        after_events.errors
+    );
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_dropping_dispatch_future_unblocks_close() -> Result<()> {
     // Caller obtains the (controller, fut) pair from dispatch() but drops
@@ -10,6 +10,9 @@ use std::process::Command;
 
 use anyhow::Context;
 use anyhow::Result;
+use chrono::DateTime;
+use chrono::SecondsFormat;
+use chrono::Utc;
 
 fn valid_git_repo() -> bool {
     matches!(Command::new("git").arg("status").status(), Ok(status) if status.success())
@@ -38,8 +41,37 @@ fn git_branch_show_current() -> Result<Option<String>> {
     }
 }
 
+/// `git rev-parse <flag>` as a path, relative to this package when git prints it that way
+/// (cargo resolves relative `rerun-if-changed` paths against the package root too).
+fn git_rev_parse_path(flag: &str) -> Result<Option<PathBuf>> {
+    let output = Command::new("git")
+        .arg("rev-parse")
+        .arg(flag)
+        .output()
+        .with_context(|| format!("Failed to run `git rev-parse {flag}`"))?;
+
+    if !output.status.success() {
+        return Ok(None);
+    }
+
+    let path = String::from_utf8(output.stdout)
+        .context("valid UTF-8")?
+        .trim()
+        .to_owned();
+
+    if path.is_empty() {
+        Ok(None)
+    } else {
+        Ok(Some(PathBuf::from(path)))
+    }
+}
+
+/// In a linked worktree or submodule `.git` is a file pointing elsewhere, so ask git for the
+/// real locations: `HEAD` and `index` are per-worktree, refs live in the shared common dir.
 fn rerun_if_git_ref_changed() -> Result<()> {
-    let git_dir = Path::new("..").join("..").join(".git");
+    let git_dir =
+        git_rev_parse_path("--git-dir")?.unwrap_or_else(|| Path::new("..").join("..").join(".git"));
+    let common_dir = git_rev_parse_path("--git-common-dir")?.unwrap_or_else(|| git_dir.clone());
 
     let head_path = git_dir.join("HEAD");
     if head_path.exists() {
@@ -47,7 +79,7 @@ fn rerun_if_git_ref_changed() -> Result<()> {
     }
 
     if let Some(current_branch) = git_branch_show_current()? {
-        let git_current_branch_ref = git_dir.join("refs").join("heads").join(current_branch);
+        let git_current_branch_ref = common_dir.join("refs").join("heads").join(current_branch);
         if git_current_branch_ref.exists() {
             println!(
                 "cargo:rerun-if-changed={}",
@@ -56,7 +88,14 @@ fn rerun_if_git_ref_changed() -> Result<()> {
         }
     }
 
-    let tags_path = git_dir.join("refs").join("tags");
+    // Staging or committing rewrites the index, which is as close as cargo
+    // gets to noticing a change in the dirty flag.
+    let index_path = git_dir.join("index");
+    if index_path.exists() {
+        println!("cargo:rerun-if-changed={}", index_path.display());
+    }
+
+    let tags_path = common_dir.join("refs").join("tags");
     if tags_path.exists() {
         println!("cargo:rerun-if-changed={}", tags_path.display());
     }
@@ -87,13 +126,16 @@ fn git_describe_tags() -> Result<Option<String>> {
     }
 }
 
-fn git_rev_parse_commit_hash() -> Result<Option<String>> {
-    let output = Command::new("git")
-        .arg("rev-parse")
-        .arg("--short")
+fn git_rev_parse_commit_hash(short: bool) -> Result<Option<String>> {
+    let mut command = Command::new("git");
+    command.arg("rev-parse");
+    if short {
+        command.arg("--short");
+    }
+    let output = command
         .arg("HEAD^{commit}")
         .output()
-        .context("Failed to run `git rev-parse --short HEAD^{commit}`")?;
+        .context("Failed to run `git rev-parse HEAD^{commit}`")?;
 
     if !output.status.success() {
         return Ok(None);
@@ -111,21 +153,79 @@ fn git_rev_parse_commit_hash() -> Result<Option<String>> {
     }
 }
 
+/// Tracked files with uncommitted changes; untracked files don't count, matching
+/// `git describe --dirty`.
+fn git_worktree_dirty() -> Result<bool> {
+    let output = Command::new("git")
+        .arg("status")
+        .arg("--porcelain")
+        .arg("--untracked-files=no")
+        .output()
+        .context("Failed to run `git status --porcelain`")?;
+
+    if !output.status.success() {
+        return Ok(false);
+    }
+
+    Ok(!output.stdout.iter().all(u8::is_ascii_whitespace))
+}
+
+/// `NODE_REQWEST_VERSION` wins over git so packagers building from a tarball can stamp the
+/// release; it is written verbatim.
 fn get_version() -> Result<String> {
+    println!("cargo:rerun-if-env-changed=NODE_REQWEST_VERSION");
+    if let Ok(version) = env::var("NODE_REQWEST_VERSION")
+        && !version.is_empty()
+    {
+        return Ok(version);
+    }
     if valid_git_repo() {
         rerun_if_git_ref_changed()?;
-        if let Some(tag) = git_describe_tags()? {
-            return Ok(tag);
-        }
-        if let Some(hash) = git_rev_parse_commit_hash()? {
-            return Ok(hash);
+        let version = match git_describe_tags()? {
+            Some(tag) => Some(tag),
+            None => git_rev_parse_commit_hash(true)?,
+        };
+        if let Some(mut version) = version {
+            if git_worktree_dirty()? {
+                version.push_str("-dirty");
+            }
+            return Ok(version);
         }
     }
     Ok("undefined".to_owned())
 }
 
+fn get_commit() -> Result<String> {
+    if valid_git_repo()
+        && let Some(hash) = git_rev_parse_commit_hash(false)?
+    {
+        return Ok(hash);
+    }
+    Ok("undefined".to_owned())
+}
+
+/// RFC 3339 UTC time of this build, pinned by `SOURCE_DATE_EPOCH` (seconds since the Unix
+/// epoch) for reproducible builds.
+fn get_build_timestamp() -> Result<String> {
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+    let timestamp = match env::var("SOURCE_DATE_EPOCH") {
+        Ok(epoch) => {
+            let seconds: i64 = epoch
+                .trim()
+                .parse()
+                .with_context(|| format!("SOURCE_DATE_EPOCH is not an integer: {epoch:?}"))?;
+            DateTime::<Utc>::from_timestamp(seconds, 0)
+                .with_context(|| format!("SOURCE_DATE_EPOCH is out of range: {seconds}"))?
+        },
+        Err(_) => Utc::now(),
+    };
+    Ok(timestamp.to_rfc3339_opts(SecondsFormat::Secs, true))
+}
+
 fn main() -> Result<()> {
     let out_dir = PathBuf::from(env::var_os("OUT_DIR").context("OUT_DIR is set by cargo")?);
     fs::write(out_dir.join("version.txt"), get_version()?)?;
+    fs::write(out_dir.join("commit.txt"), get_commit()?)?;
+    fs::write(out_dir.join("build_timestamp.txt"), get_build_timestamp()?)?;
     Ok(())
 }
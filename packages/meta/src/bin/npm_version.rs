@@ -1,13 +1,58 @@
 // SPDX-License-Identifier: Apache-2.0 OR MIT
 
 //! Standalone binary to update package.json version from git tag.
+//!
+//! Usage: `npm_version [--dry-run] [<version> | --bump <major|minor|patch>]`.
+//! With no argument the git-derived [`SEMVER`] is used; `<version>` may omit
+//! the leading `v`, and `--bump` increments [`SEMVER`], failing when there is
+//! no release tag to start from. `--dry-run` prints the
+//! version that would be set and leaves `package.json` untouched.
+
+use std::io::Write;
+use std::io::stdout;
 
 use anyhow::Result;
+use anyhow::bail;
 use meta::SEMVER;
+use meta::Version;
 use meta::npm_version;
 
+fn target_version(mut args: impl Iterator<Item = String>) -> Result<Version> {
+    let version = match args.next().as_deref() {
+        None => SEMVER.unwrap_or_default(),
+        Some("--bump") => {
+            // Bumping `0.0.0` on a dirty or untagged checkout would silently
+            // publish a version unrelated to the last release.
+            let Some(current) = SEMVER else {
+                bail!("--bump: no release tag to bump from (checkout is untagged or dirty)");
+            };
+            match args.next().as_deref() {
+                Some("major") => current.bump_major(),
+                Some("minor") => current.bump_minor(),
+                Some("patch") => current.bump_patch(),
+                other => bail!("--bump expects major, minor or patch, got {other:?}"),
+            }
+        },
+        Some(arg) => match Version::parse_lenient(arg) {
+            Some(version) => version,
+            None => bail!("invalid version argument {arg:?}"),
+        },
+    };
+    if let Some(extra) = args.next() {
+        bail!("unexpected argument {extra:?}");
+    }
+    Ok(version)
+}
+
 fn main() -> Result<()> {
-    let version = SEMVER.unwrap_or_default();
-    npm_version(&version)?;
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let before = args.len();
+    args.retain(|arg| arg != "--dry-run");
+    let dry_run = args.len() != before;
+    let version = target_version(args.into_iter())?;
+    npm_version(&version, dry_run)?;
+    if dry_run {
+        writeln!(stdout(), "{version}")?;
+    }
     Ok(())
 }
@@ -2,13 +2,18 @@
 
 //! Module with the relevant metadata and helper methods for build.rs files.
 
+use core::cmp::Ordering;
 use core::fmt;
 use std::env::var;
+use std::fs::read_dir;
 use std::fs::read_to_string;
 use std::fs::write;
+use std::path::Path;
+use std::path::PathBuf;
 
 use anyhow::Context;
 use anyhow::Result;
+use anyhow::bail;
 use chrono::Datelike;
 use tauri_winres::VersionInfo;
 use tauri_winres::WindowsResource;
@@ -16,11 +21,96 @@ use tauri_winres::WindowsResource;
 /// Git tag (release build) or commit hash (dev build), or "undefined" when no git context available.
 pub const VERSION: &str = include_str!(concat!(env!("OUT_DIR"), "/version.txt"));
 
+/// Full hash of the commit being built, or "undefined" when no git context available.
+pub const COMMIT: &str = include_str!(concat!(env!("OUT_DIR"), "/commit.txt"));
+
+/// RFC 3339 UTC time when the build script last ran (`SOURCE_DATE_EPOCH` when set), e.g.
+/// "2025-01-31T12:00:00Z".
+pub const BUILD_TIMESTAMP: &str = include_str!(concat!(env!("OUT_DIR"), "/build_timestamp.txt"));
+
 /// Structured semantic version parsed from VERSION, or None if VERSION is not a semantic version tag.
 pub const SEMVER: Option<Version> = Version::parse(VERSION);
 
+/// Maximum length in bytes of a pre-release or build-metadata label.
+pub const LABEL_CAPACITY: usize = 64;
+
+/// Dot-separated semver identifiers (`rc.1`, `build.42`) stored inline so that
+/// [`Version`] stays `Copy` and can be produced by a `const fn`.
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Label {
+    bytes: [u8; LABEL_CAPACITY],
+    len: u8,
+}
+
+impl Label {
+    /// Validate and store `s`: non-empty identifiers of `[0-9A-Za-z-]`
+    /// separated by single dots, at most [`LABEL_CAPACITY`] bytes.
+    #[must_use]
+    pub const fn new(s: &str) -> Option<Self> {
+        let src = s.as_bytes();
+        if src.is_empty() || src.len() > LABEL_CAPACITY {
+            return None;
+        }
+
+        let mut bytes = [0; LABEL_CAPACITY];
+        let mut i = 0;
+        let mut identifier_len = 0;
+        while i < src.len() {
+            let b = src[i];
+            if b == b'.' {
+                if identifier_len == 0 {
+                    return None;
+                }
+                identifier_len = 0;
+            } else if b.is_ascii_alphanumeric() || b == b'-' {
+                identifier_len += 1;
+            } else {
+                return None;
+            }
+            bytes[i] = b;
+            i += 1;
+        }
+        if identifier_len == 0 {
+            return None;
+        }
+
+        #[expect(
+            clippy::cast_possible_truncation,
+            reason = "length is bounded by LABEL_CAPACITY"
+        )]
+        let len = src.len() as u8;
+        Some(Self { bytes, len })
+    }
+
+    /// The label text, without the leading `-` / `+`.
+    #[must_use]
+    pub const fn as_str(&self) -> &str {
+        let (used, _) = self.bytes.split_at(self.len as usize);
+        match core::str::from_utf8(used) {
+            Ok(s) => s,
+            // `new` only admits ASCII, so this branch is unreachable.
+            Err(_) => "",
+        }
+    }
+}
+
+impl fmt::Debug for Label {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for Label {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 /// Semantic version structure
-#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+///
+/// Equality and ordering ignore [`Version::build`], as the semver spec
+/// requires; a pre-release sorts below its release (`1.0.0-rc1 < 1.0.0`).
+#[derive(Debug, Copy, Clone, Default)]
 #[non_exhaustive]
 pub struct Version {
     /// Major version number
@@ -29,11 +119,66 @@ pub struct Version {
     pub minor: u64,
     /// Patch version number
     pub patch: u64,
+    /// Pre-release label (`rc.1` in `v1.2.3-rc.1`)
+    pub pre: Option<Label>,
+    /// Build metadata (`build.42` in `v1.2.3+build.42`)
+    pub build: Option<Label>,
+}
+
+impl PartialEq for Version {
+    fn eq(&self, other: &Self) -> bool {
+        self.major == other.major
+            && self.minor == other.minor
+            && self.patch == other.patch
+            && self.pre == other.pre
+    }
+}
+
+impl Eq for Version {}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.cmp_precedence(other)
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Serializes to the [`Display`](fmt::Display) form, `"X.Y.Z[-pre][+build]"`.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Version {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Deserializes through [`Version::parse_lenient`], so the accepted grammar
+/// can't drift from tag parsing: both `"1.2.3"` (what `Serialize` writes) and
+/// `"v1.2.3"` (a git tag) are accepted.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Version {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = std::borrow::Cow::<'de, str>::deserialize(deserializer)?;
+        Version::parse_lenient(&s).ok_or_else(|| {
+            serde::de::Error::invalid_value(serde::de::Unexpected::Str(&s), &"a semantic version")
+        })
+    }
 }
 
 impl fmt::Display for Version {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        if let Some(pre) = &self.pre {
+            write!(f, "-{pre}")?;
+        }
+        if let Some(build) = &self.build {
+            write!(f, "+{build}")?;
+        }
+        Ok(())
     }
 }
 
@@ -45,55 +190,434 @@ impl Version {
             major,
             minor,
             patch,
+            pre: None,
+            build: None,
+        }
+    }
+
+    /// Same version with the given pre-release label.
+    #[must_use]
+    pub const fn with_pre(self, pre: Label) -> Self {
+        Self {
+            pre: Some(pre),
+            ..self
         }
     }
 
-    /// Parse version in "vX.Y.Z" format from string slice
+    /// Semver precedence: numeric major/minor/patch, then pre-release (a
+    /// release outranks any of its pre-releases). Build metadata is ignored.
+    const fn cmp_precedence(&self, other: &Self) -> Ordering {
+        let core = cmp_u64(self.major, other.major)
+            .then(cmp_u64(self.minor, other.minor))
+            .then(cmp_u64(self.patch, other.patch));
+        if !matches!(core, Ordering::Equal) {
+            return core;
+        }
+        match (&self.pre, &other.pre) {
+            (None, None) => Ordering::Equal,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(_), None) => Ordering::Less,
+            (Some(a), Some(b)) => cmp_pre_release(a, b),
+        }
+    }
+
+    /// `self >= other` by semver precedence, usable in const context:
+    ///
+    /// ```
+    /// use meta::Version;
+    ///
+    /// const NEW_ENOUGH: bool = Version::new(1, 2, 3).at_least(&Version::new(1, 2, 0));
+    /// assert!(NEW_ENOUGH);
+    /// ```
     #[must_use]
-    const fn parse(s: &str) -> Option<Self> {
+    pub const fn at_least(&self, other: &Self) -> bool {
+        !matches!(self.cmp_precedence(other), Ordering::Less)
+    }
+
+    /// Pack into the 64-bit `FILEVERSION`/`PRODUCTVERSION` layout of a Windows
+    /// `VERSIONINFO` resource: four 16-bit words `major.minor.patch.0`, most
+    /// significant first, so `v1.2.3` becomes `0x0001_0002_0003_0000`.
+    ///
+    /// Components above `u16::MAX` saturate instead of bleeding into the
+    /// neighbouring word. Pre-release and build labels have no slot and are dropped.
+    #[must_use]
+    pub const fn to_win_version_hex(&self) -> u64 {
+        const WORD: u64 = u16::MAX as u64;
+        let major = if self.major > WORD { WORD } else { self.major };
+        let minor = if self.minor > WORD { WORD } else { self.minor };
+        let patch = if self.patch > WORD { WORD } else { self.patch };
+        (major << 48) | (minor << 32) | (patch << 16)
+    }
+
+    /// Next major release: `v1.2.3` becomes `v2.0.0`.
+    ///
+    /// Like the other bumps, this drops any pre-release and build labels, and
+    /// a component already at `u64::MAX` stays there.
+    #[must_use]
+    pub const fn bump_major(self) -> Self {
+        Self::new(self.major.saturating_add(1), 0, 0)
+    }
+
+    /// Next minor release: `v1.2.3` becomes `v1.3.0`.
+    #[must_use]
+    pub const fn bump_minor(self) -> Self {
+        Self::new(self.major, self.minor.saturating_add(1), 0)
+    }
+
+    /// Next patch release: `v1.2.3` becomes `v1.2.4`.
+    #[must_use]
+    pub const fn bump_patch(self) -> Self {
+        Self::new(self.major, self.minor, self.patch.saturating_add(1))
+    }
+
+    /// Same version with the given build metadata.
+    #[must_use]
+    pub const fn with_build(self, build: Label) -> Self {
+        Self {
+            build: Some(build),
+            ..self
+        }
+    }
+
+    /// Parse a git tag of the form `vX.Y.Z`, optionally followed by `-<pre>`
+    /// and then `+<build>` (`v1.2.3-rc.1+build.42`).
+    ///
+    /// Both labels follow semver: dot-separated `[0-9A-Za-z-]` identifiers. The
+    /// `git describe` forms `vX.Y.Z-<count>-g<hash>` and
+    /// `vX.Y.Z-<pre>-<count>-g<hash>` are *not* releases and are rejected, as
+    /// is any version built from a dirty tree (`-dirty` suffix), commit
+    /// hashes, and anything with trailing garbage.
+    #[must_use]
+    pub const fn parse(s: &str) -> Option<Self> {
         let bytes = s.as_bytes();
-        if bytes.len() < 6 || bytes[0] != b'v' {
+        if bytes.is_empty() || bytes[0] != b'v' {
             return None;
         }
+        Self::parse_from(bytes, 1)
+    }
+
+    /// Like [`Version::parse`], but the leading `v` is optional, so bare
+    /// `package.json` versions (`1.2.3`) parse too. At most one `v` is
+    /// stripped: `vv1.2.3` is still rejected.
+    #[must_use]
+    pub const fn parse_lenient(s: &str) -> Option<Self> {
+        let bytes = s.as_bytes();
+        if !bytes.is_empty() && bytes[0] == b'v' {
+            Self::parse_from(bytes, 1)
+        } else {
+            Self::parse_from(bytes, 0)
+        }
+    }
 
-        let mut offset = 1;
-        let mut version = [0; 3];
+    /// Grammar shared by both parsers, starting at the first digit.
+    const fn parse_from(bytes: &[u8], start: usize) -> Option<Self> {
+        let mut offset = start;
+        let mut version = [0u64; 3];
         let mut segment = 0usize;
+        let mut digits = 0usize;
 
-        while offset < bytes.len() && segment < 3 {
-            if bytes[offset] == b'.' {
+        while offset < bytes.len() {
+            let b = bytes[offset];
+            if b == b'.' && segment < 2 {
+                if digits == 0 {
+                    return None;
+                }
                 segment += 1;
-            } else if bytes[offset] < b'0' || bytes[offset] > b'9' {
-                return None;
+                digits = 0;
+            } else if b.is_ascii_digit() {
+                let Some(shifted) = version[segment].checked_mul(10) else {
+                    return None;
+                };
+                let Some(value) = shifted.checked_add((b - b'0') as u64) else {
+                    return None;
+                };
+                version[segment] = value;
+                digits += 1;
             } else {
-                version[segment] = version[segment] * 10 + (bytes[offset] - b'0') as u64;
+                break;
             }
             offset += 1;
         }
 
-        if segment != 2 {
+        if segment != 2 || digits == 0 {
             return None;
         }
 
+        let (_, rest) = bytes.split_at(offset);
+        let mut plus = 0;
+        while plus < rest.len() && rest[plus] != b'+' {
+            plus += 1;
+        }
+        let (pre_part, build_part) = rest.split_at(plus);
+
+        let pre = if pre_part.is_empty() {
+            None
+        } else {
+            let (dash, pre_part) = pre_part.split_at(1);
+            if dash[0] != b'-' || is_git_describe_suffix(pre_part) || is_dirty_suffix(pre_part) {
+                return None;
+            }
+            let Some(label) = label_from_bytes(pre_part) else {
+                return None;
+            };
+            Some(label)
+        };
+
+        let build = if build_part.is_empty() {
+            None
+        } else {
+            let (_, build_part) = build_part.split_at(1);
+            let Some(label) = label_from_bytes(build_part) else {
+                return None;
+            };
+            Some(label)
+        };
+
         Some(Version {
             major: version[0],
             minor: version[1],
             patch: version[2],
+            pre,
+            build,
         })
     }
 }
 
-/// Override `package.json` version with the given version.
+/// `dirty` or `<anything>-dirty`: the marker build.rs appends for a dirty tree.
+const fn is_dirty_suffix(s: &[u8]) -> bool {
+    const DIRTY: &[u8] = b"dirty";
+    if s.len() < DIRTY.len() {
+        return false;
+    }
+    let start = s.len() - DIRTY.len();
+    let mut i = 0;
+    while i < DIRTY.len() {
+        if s[start + i] != DIRTY[i] {
+            return false;
+        }
+        i += 1;
+    }
+    start == 0 || s[start - 1] == b'-'
+}
+
+const fn label_from_bytes(bytes: &[u8]) -> Option<Label> {
+    match core::str::from_utf8(bytes) {
+        Ok(s) => Label::new(s),
+        Err(_) => None,
+    }
+}
+
+const fn cmp_u64(a: u64, b: u64) -> Ordering {
+    if a < b {
+        Ordering::Less
+    } else if a > b {
+        Ordering::Greater
+    } else {
+        Ordering::Equal
+    }
+}
+
+/// Plain byte-wise lexical comparison; a strict prefix sorts first.
+const fn cmp_bytes(a: &[u8], b: &[u8]) -> Ordering {
+    let mut i = 0;
+    while i < a.len() && i < b.len() {
+        if a[i] != b[i] {
+            return if a[i] < b[i] {
+                Ordering::Less
+            } else {
+                Ordering::Greater
+            };
+        }
+        i += 1;
+    }
+    cmp_u64(a.len() as u64, b.len() as u64)
+}
+
+const fn is_numeric(identifier: &[u8]) -> bool {
+    let mut i = 0;
+    while i < identifier.len() {
+        if !identifier[i].is_ascii_digit() {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// Semver §11.4.1–3: numeric identifiers compare numerically and sort below
+/// alphanumeric ones, which compare lexically. Numerically equal identifiers
+/// that differ only in leading zeros fall back to lexical order so the result
+/// stays consistent with `Eq`.
+const fn cmp_identifiers(a: &[u8], b: &[u8]) -> Ordering {
+    match (is_numeric(a), is_numeric(b)) {
+        (true, true) => {
+            let (mut a_digits, mut b_digits) = (a, b);
+            while a_digits.len() > 1 && a_digits[0] == b'0' {
+                a_digits = a_digits.split_at(1).1;
+            }
+            while b_digits.len() > 1 && b_digits[0] == b'0' {
+                b_digits = b_digits.split_at(1).1;
+            }
+            // Longer digit strings are larger; equal lengths compare lexically.
+            match cmp_u64(a_digits.len() as u64, b_digits.len() as u64) {
+                Ordering::Equal => match cmp_bytes(a_digits, b_digits) {
+                    Ordering::Equal => cmp_bytes(a, b),
+                    ord => ord,
+                },
+                ord => ord,
+            }
+        },
+        (true, false) => Ordering::Less,
+        (false, true) => Ordering::Greater,
+        (false, false) => cmp_bytes(a, b),
+    }
+}
+
+/// Precedence of two pre-release labels, identifier by identifier; when one
+/// is a prefix of the other the shorter label sorts first (§11.4.4).
+const fn cmp_pre_release(a: &Label, b: &Label) -> Ordering {
+    let (mut a, mut b) = (a.as_str().as_bytes(), b.as_str().as_bytes());
+    loop {
+        match (a.is_empty(), b.is_empty()) {
+            (true, true) => return Ordering::Equal,
+            (true, false) => return Ordering::Less,
+            (false, true) => return Ordering::Greater,
+            (false, false) => {},
+        }
+        let (a_identifier, a_rest) = a.split_at(identifier_end(a));
+        let (b_identifier, b_rest) = b.split_at(identifier_end(b));
+        match cmp_identifiers(a_identifier, b_identifier) {
+            Ordering::Equal => {},
+            ord => return ord,
+        }
+        a = skip_dot(a_rest);
+        b = skip_dot(b_rest);
+    }
+}
+
+/// Length of the first dot-separated identifier.
+const fn identifier_end(label: &[u8]) -> usize {
+    let mut end = 0;
+    while end < label.len() && label[end] != b'.' {
+        end += 1;
+    }
+    end
+}
+
+const fn skip_dot(rest: &[u8]) -> &[u8] {
+    if rest.is_empty() {
+        rest
+    } else {
+        rest.split_at(1).1
+    }
+}
+
+/// Ends in `<count>-g<hex>`: the commits-since-tag suffix appended by `git
+/// describe`, either alone or after a pre-release (`rc1-2-ge6a4f89`).
+const fn is_git_describe_suffix(s: &[u8]) -> bool {
+    let mut i = s.len();
+    while i > 0 && s[i - 1].is_ascii_hexdigit() {
+        i -= 1;
+    }
+    if i == s.len() || i < 3 || s[i - 1] != b'g' || s[i - 2] != b'-' {
+        return false;
+    }
+    i -= 2;
+    let count_end = i;
+    while i > 0 && s[i - 1].is_ascii_digit() {
+        i -= 1;
+    }
+    i < count_end && (i == 0 || s[i - 1] == b'-')
+}
+
+/// Override `package.json` version in the current working directory with the given version.
+///
+/// Thin wrapper over [`npm_version_in`].
+pub fn npm_version(version: &Version, dry_run: bool) -> Result<()> {
+    npm_version_in(Path::new("."), version, dry_run)
+}
+
+/// Override `dir/package.json` version with the given version.
 ///
 /// This implementation uses `serde_json` with the `preserve_order` feature to ensure that the
 /// `package.json` file is updated without changing the order of entries or significantly
 /// altering the formatting (it uses standard 2-space indentation).
-pub fn npm_version(version: &Version) -> Result<()> {
-    let path = "package.json";
-    let content = read_to_string(path).context(format!("Failed to read {path}"))?;
+///
+/// With `dry_run` the file is still read and parsed, so a missing or malformed `package.json`
+/// fails the same way, but nothing is written.
+pub fn npm_version_in(dir: &Path, version: &Version, dry_run: bool) -> Result<()> {
+    set_package_version(&dir.join("package.json"), version, dry_run)?;
+    Ok(())
+}
 
-    let mut json: serde_json::Value =
-        serde_json::from_str(&content).context(format!("Failed to parse {path}"))?;
+/// Override the version of `dir/package.json` and of every workspace member it lists.
+///
+/// Members come from the root `workspaces` field (npm's array form or yarn's
+/// `{ "packages": [...] }` form). Entries may be plain directories or `dir/*`, which
+/// matches every direct subdirectory containing a `package.json`.
+///
+/// `dry_run` behaves as in [`npm_version_in`]: every member is still resolved and parsed.
+pub fn npm_version_workspaces_in(dir: &Path, version: &Version, dry_run: bool) -> Result<()> {
+    let root = set_package_version(&dir.join("package.json"), version, dry_run)?;
+    for member in workspace_members(dir, &root)? {
+        set_package_version(&member.join("package.json"), version, dry_run)?;
+    }
+    Ok(())
+}
+
+fn workspace_members(dir: &Path, root: &serde_json::Value) -> Result<Vec<PathBuf>> {
+    let patterns = match root.get("workspaces") {
+        None => return Ok(Vec::new()),
+        Some(serde_json::Value::Array(patterns)) => patterns,
+        Some(serde_json::Value::Object(obj)) => match obj.get("packages") {
+            Some(serde_json::Value::Array(patterns)) => patterns,
+            _ => bail!("`workspaces.packages` must be an array of paths"),
+        },
+        Some(_) => bail!("`workspaces` must be an array of paths"),
+    };
+
+    let mut members = Vec::new();
+    for pattern in patterns {
+        let pattern = pattern
+            .as_str()
+            .context("`workspaces` entries must be strings")?;
+        match pattern.strip_suffix("/*") {
+            Some(parent) if !parent.contains('*') => {
+                let parent = dir.join(parent);
+                let entries = read_dir(&parent)
+                    .with_context(|| format!("Failed to list {}", parent.display()))?;
+                let mut found = Vec::new();
+                for entry in entries {
+                    let path = entry
+                        .with_context(|| format!("Failed to list {}", parent.display()))?
+                        .path();
+                    if path.join("package.json").is_file() {
+                        found.push(path);
+                    }
+                }
+                found.sort();
+                members.extend(found);
+            },
+            _ if pattern.contains('*') => {
+                bail!("unsupported workspaces pattern {pattern:?}: use `dir` or `dir/*`")
+            },
+            _ => members.push(dir.join(pattern)),
+        }
+    }
+    Ok(members)
+}
+
+/// Rewrite the `version` field of one `package.json`, returning the updated document.
+fn set_package_version(path: &Path, version: &Version, dry_run: bool) -> Result<serde_json::Value> {
+    let content =
+        read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+
+    let mut json: serde_json::Value = serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse {}", path.display()))?;
+
+    if dry_run {
+        return Ok(json);
+    }
 
     if let Some(obj) = json.as_object_mut() {
         obj.insert(
@@ -102,12 +626,58 @@ pub fn npm_version(version: &Version) -> Result<()> {
         );
     }
 
-    let mut new_content =
-        serde_json::to_string_pretty(&json).context(format!("Failed to serialize {path}"))?;
+    let mut new_content = serde_json::to_string_pretty(&json)
+        .with_context(|| format!("Failed to serialize {}", path.display()))?;
     new_content.push('\n');
-    write(path, new_content).context(format!("Failed to write {path}"))?;
+    write(path, new_content).with_context(|| format!("Failed to write {}", path.display()))?;
 
-    Ok(())
+    Ok(json)
+}
+
+/// Application manifest opting into long paths and running at the caller's privilege level.
+///
+/// Windows honours `longPathAware` from the manifest of the process executable, so this
+/// matters most for binaries; a DLL only carries it for tools that inspect the resource.
+pub const DEFAULT_MANIFEST: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<assembly xmlns="urn:schemas-microsoft-com:asm.v1" manifestVersion="1.0">
+  <trustInfo xmlns="urn:schemas-microsoft-com:asm.v3">
+    <security>
+      <requestedPrivileges>
+        <requestedExecutionLevel level="asInvoker" uiAccess="false"/>
+      </requestedPrivileges>
+    </security>
+  </trustInfo>
+  <application xmlns="urn:schemas-microsoft-com:asm.v3">
+    <windowsSettings xmlns:ws2="http://schemas.microsoft.com/SMI/2016/WindowsSettings">
+      <ws2:longPathAware>true</ws2:longPathAware>
+    </windowsSettings>
+  </application>
+</assembly>
+"#;
+
+/// Publisher strings embedded in the Windows `VERSIONINFO` resource.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResourceMeta {
+    /// `CompanyName` string
+    pub company: String,
+    /// Holder named in `LegalCopyright` as "Copyright © <year> <holder>"
+    pub copyright_holder: String,
+    /// Application icon (`.ico`) embedded in the binary; `None` embeds no icon
+    pub icon: Option<PathBuf>,
+    /// Application manifest XML (e.g. [`DEFAULT_MANIFEST`]); `None` embeds no manifest
+    pub manifest: Option<String>,
+}
+
+impl Default for ResourceMeta {
+    fn default() -> Self {
+        let author = "Vadim Piven <vadim@piven.tech> (https://piven.tech)";
+        Self {
+            company: author.to_owned(),
+            copyright_holder: author.to_owned(),
+            icon: None,
+            manifest: None,
+        }
+    }
 }
 
 /// Compile resource.rc file to resource.res file and add it to linker input as described in:
@@ -115,10 +685,26 @@ pub fn npm_version(version: &Version) -> Result<()> {
 /// - <https://stackoverflow.com/questions/74509880/add-exe-file-details-to-binary-of-compiled-rust-code>
 /// - <https://learn.microsoft.com/en-us/windows/win32/menurc/versioninfo-resource>
 ///
+/// Uses the default [`ResourceMeta`]; see [`cdylib_win_rc_with`] to override it.
+///
 /// # Warning
 ///
 /// Intended for use only in build.rs
 pub fn cdylib_win_rc(product: &str, version: &Version, filename: &str) -> Result<()> {
+    cdylib_win_rc_with(product, version, filename, &ResourceMeta::default())
+}
+
+/// Same as [`cdylib_win_rc`] with caller-supplied publisher strings.
+///
+/// # Warning
+///
+/// Intended for use only in build.rs
+pub fn cdylib_win_rc_with(
+    product: &str,
+    version: &Version,
+    filename: &str,
+    meta: &ResourceMeta,
+) -> Result<()> {
     const ENGLISH_US: u16 = 0x0409;
 
     const VS_FFI_FILEFLAGSMASK: u64 = 0x0000_003F;
@@ -126,6 +712,19 @@ pub fn cdylib_win_rc(product: &str, version: &Version, filename: &str) -> Result
     const VFT_DLL: u64 = 0x0000_0002;
     const VFT2_UNKNOWN: u64 = 0x0000_0000;
 
+    // Checked on every target so a mistyped path fails the build everywhere,
+    // not only on the Windows runner.
+    let icon = match &meta.icon {
+        Some(path) if !path.is_file() => {
+            bail!("icon for {product} not found: {}", path.display())
+        },
+        Some(path) => Some(
+            path.to_str()
+                .with_context(|| format!("icon path is not UTF-8: {}", path.display()))?,
+        ),
+        None => None,
+    };
+
     if !cfg!(target_env = "msvc") {
         return Ok(());
     }
@@ -133,11 +732,14 @@ pub fn cdylib_win_rc(product: &str, version: &Version, filename: &str) -> Result
     let internal_name =
         var("CARGO_PKG_NAME").context("CARGO_PKG_NAME is set by cargo for build.rs")?;
 
-    let version_hex = (version.major << 48) | (version.minor << 32) | (version.patch << 16);
+    let version_hex = version.to_win_version_hex();
     let version_str = format!("{}.{}.{}.0", version.major, version.minor, version.patch);
 
-    let author = "Vadim Piven <vadim@piven.tech> (https://piven.tech)";
-    let copyright = format!("Copyright © {} {}", chrono::Utc::now().year(), author);
+    let copyright = format!(
+        "Copyright © {} {}",
+        chrono::Utc::now().year(),
+        meta.copyright_holder
+    );
 
     let mut res = WindowsResource::new();
     res.set_language(ENGLISH_US);
@@ -150,7 +752,7 @@ pub fn cdylib_win_rc(product: &str, version: &Version, filename: &str) -> Result
     res.set_version_info(VersionInfo::FILETYPE, VFT_DLL);
     res.set_version_info(VersionInfo::FILESUBTYPE, VFT2_UNKNOWN);
 
-    res.set("CompanyName", author);
+    res.set("CompanyName", &meta.company);
     res.set("LegalCopyright", &copyright);
     res.set("ProductName", product);
     res.set("FileDescription", product);
@@ -159,8 +761,15 @@ pub fn cdylib_win_rc(product: &str, version: &Version, filename: &str) -> Result
     res.set("ProductVersion", &version_str);
     res.set("FileVersion", &version_str);
 
+    if let Some(icon) = icon {
+        res.set_icon(icon);
+    }
+    if let Some(manifest) = &meta.manifest {
+        res.set_manifest(manifest);
+    }
+
     res.compile()
-        .context("failed to compile windows resource")?;
+        .with_context(|| format!("failed to compile windows resource for {product} {version}"))?;
 
     Ok(())
 }
@@ -175,6 +784,24 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn commit_test() {
+        assert!(
+            COMMIT == "undefined"
+                || (COMMIT.len() == 40 && COMMIT.bytes().all(|b| b.is_ascii_hexdigit())),
+            "unexpected COMMIT: {COMMIT}"
+        );
+    }
+
+    #[test]
+    fn build_timestamp_test() {
+        assert!(
+            chrono::DateTime::parse_from_rfc3339(BUILD_TIMESTAMP).is_ok(),
+            "BUILD_TIMESTAMP is not RFC 3339: {BUILD_TIMESTAMP}"
+        );
+        assert!(BUILD_TIMESTAMP.ends_with('Z'));
+    }
+
     #[test]
     fn version_formatting_test() {
         let version = Version::new(1, 0, 82);
@@ -187,13 +814,239 @@ mod tests {
         let result = Version::parse("v1.0.82");
         assert_eq!(Some(Version::new(1, 0, 82)), result);
 
-        // Git describe output with additional info (should fail)
+        // Git describe output with commit count and hash is not a release (should fail)
         let result = Version::parse("v1.0.81-2-ge6a4f89");
         assert!(result.is_none());
+        // ...including past a pre-release tag
+        let result = Version::parse("v1.0.0-rc1-2-ge6a4f89");
+        assert!(result.is_none());
+        // A pre-release merely ending in hex is still one
+        assert!(Version::parse("v1.0.0-rc1-gabc").is_some());
 
         // Commit hash (should fail)
         let result = Version::parse("c24f925");
         assert!(result.is_none());
+
+        // Dirty working tree, on a tag or past one (should fail)
+        for input in [
+            "v1.0.82-dirty",
+            "v1.0.82-rc1-dirty",
+            "v1.0.81-2-ge6a4f89-dirty",
+        ] {
+            assert!(Version::parse(input).is_none(), "{input} must not parse");
+        }
+        // `dirty` only counts as a whole identifier
+        assert!(Version::parse("v1.0.82-nondirty").is_some());
+    }
+
+    #[test]
+    fn version_lenient_parsing_test() {
+        assert_eq!(
+            Some(Version::new(1, 2, 3)),
+            Version::parse_lenient("v1.2.3")
+        );
+        assert_eq!(Some(Version::new(1, 2, 3)), Version::parse_lenient("1.2.3"));
+        assert!(Version::parse("1.2.3").is_none());
+
+        for input in ["vv1.2.3", "", "v", "1.2", "1.2.3-dirty"] {
+            assert!(
+                Version::parse_lenient(input).is_none(),
+                "{input} must not parse"
+            );
+        }
+    }
+
+    #[test]
+    fn version_pre_release_parsing_test() {
+        let rc1 = Label::new("rc1");
+        assert!(rc1.is_some());
+        let result = Version::parse("v1.2.3-rc1");
+        assert_eq!(rc1.map(|pre| Version::new(1, 2, 3).with_pre(pre)), result);
+        assert_eq!(Some("1.2.3-rc1".to_string()), result.map(|v| v.to_string()));
+
+        let result = Version::parse("v2.0.0-beta.2");
+        assert_eq!(
+            Some("beta.2"),
+            result
+                .as_ref()
+                .and_then(|v| v.pre.as_ref())
+                .map(Label::as_str)
+        );
+
+        // Hyphens are legal inside identifiers
+        assert!(Version::parse("v1.0.0-alpha-1").is_some());
+
+        // Malformed versions and labels (should fail)
+        for input in [
+            "v1.2",
+            "v1.2.3.4",
+            "v1..3",
+            "v1.2.3-",
+            "v1.2.3-rc..1",
+            "v1.2.3-rc_1",
+            "v1.2.3rc1",
+            "1.2.3",
+            "v99999999999999999999.0.0",
+        ] {
+            assert!(Version::parse(input).is_none(), "{input} must not parse");
+        }
+    }
+
+    #[test]
+    fn version_build_metadata_parsing_test() {
+        let result = Version::parse("v1.2.3+build.42");
+        assert_eq!(
+            Some("1.2.3+build.42".to_string()),
+            result.map(|v| v.to_string())
+        );
+        assert_eq!(
+            Some("build.42"),
+            result
+                .as_ref()
+                .and_then(|v| v.build.as_ref())
+                .map(Label::as_str)
+        );
+        assert_eq!(None, result.and_then(|v| v.pre));
+
+        // Pre-release and build metadata together
+        let result = Version::parse("v1.2.3-rc.1+sha.e6a4f89");
+        assert_eq!(
+            Some("1.2.3-rc.1+sha.e6a4f89".to_string()),
+            result.map(|v| v.to_string())
+        );
+        assert_eq!(Version::parse("v1.2.3-rc.1"), result);
+
+        // Build metadata is ignored for equality, pre-release is not
+        assert_eq!(Version::parse("v1.2.3+a"), Version::parse("v1.2.3+b"));
+        assert_eq!(Version::parse("v1.2.3+a"), Some(Version::new(1, 2, 3)));
+        assert_ne!(
+            Version::parse("v1.2.3-rc.1+a"),
+            Version::parse("v1.2.3-rc.2+a")
+        );
+
+        // A `-` after `+` belongs to the build label, so no pre-release is captured
+        let result = Version::parse("v1.2.3+build-7");
+        assert_eq!(None, result.and_then(|v| v.pre));
+
+        // Empty or malformed build metadata (should fail)
+        for input in ["v1.2.3+", "v1.2.3-rc.1+", "v1.2.3+a+b", "v1.2.3+.a"] {
+            assert!(Version::parse(input).is_none(), "{input} must not parse");
+        }
+    }
+
+    #[test]
+    fn version_ordering_test() {
+        let parse = |s: &str| Version::parse(s).unwrap_or_default();
+
+        assert!(parse("v1.2.0") > parse("v1.1.9"));
+        assert!(parse("v2.0.0") > parse("v1.99.99"));
+        assert!(parse("v1.0.10") > parse("v1.0.9"));
+        assert!(parse("v1.0.0-rc1") < parse("v1.0.0"));
+        assert_eq!(Ordering::Equal, parse("v1.2.3+a").cmp(&parse("v1.2.3+b")));
+
+        // Precedence chain from the semver spec, §11.4
+        let chain = [
+            "v1.0.0-alpha",
+            "v1.0.0-alpha.1",
+            "v1.0.0-alpha.beta",
+            "v1.0.0-beta",
+            "v1.0.0-beta.2",
+            "v1.0.0-beta.11",
+            "v1.0.0-rc.1",
+            "v1.0.0",
+        ];
+        for pair in chain.windows(2) {
+            assert!(parse(pair[0]) < parse(pair[1]), "{} < {}", pair[0], pair[1]);
+        }
+
+        let mut shuffled = [parse("v1.0.0"), parse("v0.9.0"), parse("v1.0.0-rc.1")];
+        shuffled.sort();
+        assert_eq!(
+            [parse("v0.9.0"), parse("v1.0.0-rc.1"), parse("v1.0.0")],
+            shuffled
+        );
+    }
+
+    #[test]
+    fn version_bump_test() {
+        let version = Version::new(1, 2, 3);
+        assert_eq!(Version::new(2, 0, 0), version.bump_major());
+        assert_eq!(Version::new(1, 3, 0), version.bump_minor());
+        assert_eq!(Version::new(1, 2, 4), version.bump_patch());
+
+        let labelled = Label::new("rc.1")
+            .zip(Label::new("build.7"))
+            .map(|(pre, build)| version.with_pre(pre).with_build(build));
+        let bumped = labelled.map(Version::bump_patch);
+        assert_eq!(Some(Version::new(1, 2, 4)), bumped);
+        assert!(bumped.is_some_and(|v| v.pre.is_none() && v.build.is_none()));
+
+        assert_eq!(u64::MAX, Version::new(u64::MAX, 0, 0).bump_major().major);
+    }
+
+    #[test]
+    fn version_at_least_test() {
+        const BASE: Version = Version::new(1, 2, 3);
+        // Equal, evaluated at compile time
+        const { assert!(BASE.at_least(&Version::new(1, 2, 3))) };
+
+        // Greater in each component
+        assert!(Version::new(2, 0, 0).at_least(&BASE));
+        assert!(Version::new(1, 3, 0).at_least(&BASE));
+        assert!(Version::new(1, 2, 4).at_least(&BASE));
+
+        // Lesser in each component
+        assert!(!Version::new(0, 9, 9).at_least(&BASE));
+        assert!(!Version::new(1, 1, 9).at_least(&BASE));
+        assert!(!Version::new(1, 2, 2).at_least(&BASE));
+
+        // A pre-release doesn't satisfy its own release
+        let rc = Version::parse("v1.2.3-rc.1").unwrap_or_default();
+        assert!(!rc.at_least(&BASE));
+        assert!(BASE.at_least(&rc));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn version_serde_round_trip_test() -> Result<()> {
+        let version = Version::parse("v1.2.3-rc.1+build.42").unwrap_or_default();
+        let json = serde_json::to_string(&version)?;
+        assert_eq!(r#""1.2.3-rc.1+build.42""#, json);
+        assert_eq!(version, serde_json::from_str::<Version>(&json)?);
+
+        // The git-tag form is accepted too
+        assert_eq!(
+            Version::new(1, 0, 82),
+            serde_json::from_str(r#""v1.0.82""#)?
+        );
+
+        // Anything `Version::parse` rejects (should fail)
+        assert!(serde_json::from_str::<Version>(r#""1.2""#).is_err());
+        assert!(serde_json::from_str::<Version>("123").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn version_win_hex_test() {
+        assert_eq!(
+            0x0001_0002_0003_0000,
+            Version::new(1, 2, 3).to_win_version_hex()
+        );
+        assert_eq!(0, Version::new(0, 0, 0).to_win_version_hex());
+        assert_eq!(
+            0xFFFF_FFFF_FFFF_0000,
+            Version::new(65535, 65535, 65535).to_win_version_hex()
+        );
+
+        // Labels don't affect the packing
+        let rc = Version::parse("v1.2.3-rc.1+build.42").unwrap_or_default();
+        assert_eq!(0x0001_0002_0003_0000, rc.to_win_version_hex());
+
+        // Oversized components saturate rather than overflow into the next word
+        assert_eq!(
+            0x0001_FFFF_0003_0000,
+            Version::new(1, 70000, 3).to_win_version_hex()
+        );
     }
 
     #[test]
@@ -212,6 +1065,32 @@ mod tests {
         let version = Version::new(1, 2, 3);
         cdylib_win_rc("TestProduct", &version, "test.dll")?;
 
+        let meta = ResourceMeta {
+            company: "Example Corp".to_owned(),
+            copyright_holder: "Example Corp and contributors".to_owned(),
+            icon: None,
+            manifest: Some(DEFAULT_MANIFEST.to_owned()),
+        };
+        cdylib_win_rc_with("TestProduct", &version, "test.dll", &meta)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn cdylib_win_rc_missing_icon_test() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let meta = ResourceMeta {
+            icon: Some(temp_dir.path().join("missing.ico")),
+            ..ResourceMeta::default()
+        };
+        let err = cdylib_win_rc_with("TestProduct", &Version::new(1, 2, 3), "test.dll", &meta)
+            .err()
+            .map(|e| e.to_string())
+            .unwrap_or_default();
+        assert!(
+            err.contains("missing.ico"),
+            "error should name the icon: {err}"
+        );
         Ok(())
     }
 }
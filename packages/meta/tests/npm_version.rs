@@ -3,18 +3,17 @@
 //! Tests for `npm_version` binary.
 
 use std::fs::File;
+use std::fs::create_dir_all;
 use std::fs::read_to_string;
 use std::io::Write;
 use std::process::Command;
 
-use anyhow::Context;
 use anyhow::Result;
 use indoc::formatdoc;
 use indoc::indoc;
 use meta::SEMVER;
 use pretty_assertions::assert_eq;
 use tempfile::tempdir;
-use with_dir::WithDir;
 
 fn normalize_newlines(s: &str) -> String {
     s.replace("\r\n", "\n").trim().to_string()
@@ -39,12 +38,7 @@ fn npm_version_test() -> Result<()> {
         normalize_newlines(&read_to_string(&package_json_path)?)
     );
 
-    WithDir::new(dir_path)
-        .map(|_| meta::npm_version(&version))
-        .context(format!(
-            "failed to switch workdir to {}",
-            dir_path.display()
-        ))??;
+    meta::npm_version_in(dir_path, &version, false)?;
 
     let expected_content = indoc! {r#"
         {
@@ -60,6 +54,83 @@ fn npm_version_test() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn npm_version_in_missing_dir_test() -> Result<()> {
+    let dir = tempdir()?;
+    let missing = dir.path().join("missing");
+    let err = meta::npm_version_in(&missing, &meta::Version::new(1, 2, 3), false)
+        .err()
+        .map(|e| e.to_string())
+        .unwrap_or_default();
+    assert!(
+        err.contains("package.json"),
+        "error should name the file: {err}"
+    );
+    Ok(())
+}
+
+#[test]
+fn npm_version_workspaces_test() -> Result<()> {
+    let dir = tempdir()?;
+    let dir_path = dir.path();
+    let version = meta::Version::new(1, 2, 3);
+
+    let root_content = indoc! {r#"
+        {
+          "name": "root",
+          "version": "0.0.0",
+          "workspaces": [
+            "packages/*"
+          ]
+        }
+    "#};
+    File::create(dir_path.join("package.json"))?.write_all(root_content.as_bytes())?;
+    for member in ["a", "b"] {
+        let member_dir = dir_path.join("packages").join(member);
+        create_dir_all(&member_dir)?;
+        let member_content = formatdoc! {r#"
+            {{
+              "name": "{member}",
+              "version": "0.0.0"
+            }}
+        "#};
+        File::create(member_dir.join("package.json"))?.write_all(member_content.as_bytes())?;
+    }
+    // Directories without a package.json are not members
+    create_dir_all(dir_path.join("packages").join("docs"))?;
+
+    // The root-only variant leaves members untouched
+    meta::npm_version_in(dir_path, &version, false)?;
+    let member_a = read_to_string(dir_path.join("packages").join("a").join("package.json"))?;
+    assert!(member_a.contains(r#""version": "0.0.0""#));
+
+    // A dry run resolves every member but writes nothing
+    let root_before = read_to_string(dir_path.join("package.json"))?;
+    meta::npm_version_workspaces_in(dir_path, &version, true)?;
+    assert_eq!(root_before, read_to_string(dir_path.join("package.json"))?);
+    let member_a = read_to_string(dir_path.join("packages").join("a").join("package.json"))?;
+    assert!(member_a.contains(r#""version": "0.0.0""#));
+
+    meta::npm_version_workspaces_in(dir_path, &version, false)?;
+    let root = read_to_string(dir_path.join("package.json"))?;
+    assert!(root.contains(r#""version": "1.2.3""#));
+    for member in ["a", "b"] {
+        let expected_content = formatdoc! {r#"
+            {{
+              "name": "{member}",
+              "version": "1.2.3"
+            }}
+        "#};
+        let path = dir_path.join("packages").join(member).join("package.json");
+        assert_eq!(
+            normalize_newlines(&expected_content),
+            normalize_newlines(&read_to_string(path)?)
+        );
+    }
+
+    Ok(())
+}
+
 #[test]
 fn npm_version_binary_test() -> Result<()> {
     let dir = tempdir()?;
@@ -92,3 +163,89 @@ fn npm_version_binary_test() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn npm_version_binary_argument_test() -> Result<()> {
+    let dir = tempdir()?;
+    let dir_path = dir.path();
+    let package_json_path = dir_path.join("package.json");
+
+    let initial_content = indoc! {r#"
+        {
+          "name": "test-package",
+          "version": "0.0.0"
+        }
+    "#};
+    File::create(&package_json_path)?.write_all(initial_content.as_bytes())?;
+
+    for args in [&["nope"][..], &["--bump", "huge"], &["1.2.3", "extra"]] {
+        let output = Command::new(env!("CARGO_BIN_EXE_npm_version"))
+            .args(args)
+            .current_dir(dir_path)
+            .output()?;
+        assert!(!output.status.success(), "{args:?} must fail");
+        assert!(
+            !output.stderr.is_empty(),
+            "{args:?} must explain the failure"
+        );
+    }
+    assert_eq!(
+        normalize_newlines(initial_content),
+        normalize_newlines(&read_to_string(&package_json_path)?)
+    );
+
+    let status = Command::new(env!("CARGO_BIN_EXE_npm_version"))
+        .arg("2.0.0-rc.1")
+        .current_dir(dir_path)
+        .status()?;
+    assert!(status.success());
+    assert!(read_to_string(&package_json_path)?.contains(r#""version": "2.0.0-rc.1""#));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_npm_version"))
+        .args(["--bump", "minor"])
+        .current_dir(dir_path)
+        .output()?;
+    if let Some(current) = SEMVER {
+        assert!(output.status.success());
+        let bumped = current.bump_minor();
+        assert!(read_to_string(&package_json_path)?.contains(&format!(r#""version": "{bumped}""#)));
+    } else {
+        assert!(!output.status.success(), "--bump needs a release tag");
+        assert!(String::from_utf8_lossy(&output.stderr).contains("no release tag"));
+        assert!(read_to_string(&package_json_path)?.contains(r#""version": "2.0.0-rc.1""#));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn npm_version_dry_run_test() -> Result<()> {
+    let dir = tempdir()?;
+    let dir_path = dir.path();
+    let package_json_path = dir_path.join("package.json");
+
+    let initial_content = indoc! {r#"
+        {
+          "name": "test-package",
+          "version": "0.0.0"
+        }
+    "#};
+    File::create(&package_json_path)?.write_all(initial_content.as_bytes())?;
+
+    meta::npm_version_in(dir_path, &meta::Version::new(1, 2, 3), true)?;
+    assert_eq!(
+        initial_content,
+        read_to_string(&package_json_path)?,
+        "dry run must not rewrite package.json"
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_npm_version"))
+        .args(["--dry-run", "v4.5.6"])
+        .current_dir(dir_path)
+        .output()?;
+    assert!(output.status.success());
+    assert_eq!("4.5.6", String::from_utf8(output.stdout)?.trim());
+    assert_eq!(initial_content, read_to_string(&package_json_path)?);
+
+    Ok(())
+}
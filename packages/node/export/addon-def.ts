@@ -19,8 +19,23 @@ export type AgentProxyOption =
       uri: string;
       headers: Record<string, string>;
       auth: AgentProxyAuth | null;
+      /** `NO_PROXY`-style bypass patterns, one per entry (max 64). */
+      noProxy: string[];
     };
 
+/** PEM client certificate and private key for mutual TLS. */
+export type AgentClientIdentity = {
+  cert: string;
+  key: string;
+};
+
+/** One static DNS override: connect to `addr` whenever `host` is requested. */
+export type AgentResolveEntry = {
+  host: string;
+  port: number;
+  addr: string;
+};
+
 /**
  * Per-Agent configuration crossing the FFI at `agentCreate`. Every field is
  * either a primitive or `null` so the JSON shape stays stable. Timeouts are
@@ -31,30 +46,68 @@ export type AgentCreationOptions = {
   allowH2: boolean;
   /** Enable Happy-Eyeballs / `auto-select-family` semantics on connect. */
   autoSelectFamily: boolean;
+  /** Decode brotli responses; `null` = on when compiled in. */
+  brotli: boolean | null;
   /** Default per-request body-idle timeout (ms between chunks). */
   bodyTimeout: number | null;
   /** Additional trust roots as PEM strings (max 32 entries, 256 KiB each). */
   ca: string[];
+  /** Persist `Set-Cookie` responses and replay them on later dispatches. */
+  cookies: boolean;
   /** TCP/TLS handshake timeout (ms). */
   connectTimeout: number | null;
+  /** Lowercase-keyed headers added to every request lacking the same name (max 64). */
+  defaultHeaders: Record<string, string>;
   /** Default per-request headers timeout (ms from connect to first byte). */
   headersTimeout: number | null;
+  /** Reject dispatches and redirects to non-`https` URLs. */
+  httpsOnly: boolean;
+  /** HTTP/2 PING interval (ms); `null` = no pings. */
+  http2KeepAliveInterval: number | null;
+  /** HTTP/2 PING ack deadline (ms); `null` = reqwest default (20 s). */
+  http2KeepAliveTimeout: number | null;
+  /** Keep sending HTTP/2 PINGs on connections with no open streams. */
+  http2KeepAliveWhileIdle: boolean;
+  /** Speak HTTP/2 without ALPN negotiation (h2c or pinned h2). Requires `allowH2`. */
+  http2PriorKnowledge: boolean;
+  /** Client certificate for mutual TLS (each half max 256 KiB); `null` = none. */
+  identity: AgentClientIdentity | null;
   /** Idle connection lifetime in the pool (ms). */
   keepAliveTimeout: number | null;
+  /** Decode gzip responses; `null` = on when compiled in. */
+  gzip: boolean | null;
   /** Source IPv4/IPv6 address for outgoing sockets (string form). */
   localAddress: string | null;
+  /** Requests executing at once (`null` = unlimited); extras wait FIFO. */
+  maxConcurrent: number | null;
+  /** Requests allowed to wait for a `maxConcurrent` slot (`null` = unbounded). */
+  maxQueue: number | null;
   /** Max redirect hops (`0` = follow none, undici default). */
   maxRedirections: number;
   /** Cap on decoded response body in bytes (`null` = uncapped). */
   maxResponseSize: number | null;
+  /** Decode deflate responses; `null` = on when compiled in. */
+  deflate: boolean | null;
+  /** Cap on idle pooled connections per host (`null` = unbounded). */
+  poolMaxIdlePerHost: number | null;
   /** Upstream proxy (no-proxy / system / custom URI). */
   proxy: AgentProxyOption;
+  /** Static DNS overrides (max 64); `port: 0` keeps the request URL's port. */
+  resolve: AgentResolveEntry[];
   /** Verify the server certificate hostname against the SAN. */
   rejectInvalidHostnames: boolean;
   /** Verify the server certificate chain against the trust store. */
   rejectUnauthorized: boolean;
+  /** TCP keep-alive probe interval (ms); `null` = OS default. */
+  tcpKeepAlive: number | null;
+  /** Disable Nagle's algorithm (`TCP_NODELAY`). */
+  tcpNoDelay: boolean;
   /** Total per-request deadline (ms) including connect, headers, and body. */
   timeout: number | null;
+  /** `User-Agent` for requests that don't set one; `null` = `node_reqwest/<version>`. */
+  userAgent: string | null;
+  /** Decode zstd responses; `null` = on when compiled in. */
+  zstd: boolean | null;
 };
 
 /**
@@ -103,12 +156,28 @@ export type DispatchCallbacks = {
     statusCode: number,
     headers: Record<string, string | string[]>,
     statusMessage: string,
+    /** Peer `ip:port`, when the connector reports it. */
+    remoteAddress: string | undefined,
   ) => void;
   onResponseData: (requestId: number, chunk: Uint8Array) => void;
   onResponseEnd: (requestId: number, trailers: Record<string, string | string[]>) => void;
   onResponseError: (requestId: number, error: CoreErrorInfo) => void;
 };
 
+/** Native-side Agent counters returned by `agentStats`. */
+export type AgentNativeStats = {
+  /** Dispatches accepted by Rust whose request future hasn't finished. */
+  inFlight: number;
+};
+
+/** Build identifiers of the loaded native module. */
+export type NativeVersion = {
+  /** Git tag (release build) or short commit hash, `-dirty` when built from a modified tree. */
+  version: string;
+  /** Full commit hash, or `"undefined"` for builds outside a git checkout. */
+  commit: string;
+};
+
 export interface Addon {
   agentCreate(options: AgentCreationOptions, callbacks: DispatchCallbacks): AgentHandle;
   agentDispatch(
@@ -118,8 +187,13 @@ export interface Addon {
   ): RequestHandle;
   agentClose(agent: AgentHandle): Promise<void>;
   agentDestroy(agent: AgentHandle): Promise<void>;
+  agentStats(agent: AgentHandle): AgentNativeStats;
+  agentAbortAll(agent: AgentHandle): void;
 
   requestHandleAbort(handle: RequestHandle): void;
   requestHandlePause(handle: RequestHandle): void;
   requestHandleResume(handle: RequestHandle): void;
+
+  version(): NativeVersion;
+  compareVersions(a: string, b: string): -1 | 0 | 1;
 }
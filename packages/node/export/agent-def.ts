@@ -6,6 +6,13 @@ import type { ConnectionOptions as TlsConnectionOptions } from "node:tls";
 export type TlsOptions = Pick<TlsConnectionOptions, "ca" | "rejectUnauthorized"> & {
   /** Verify the server certificate hostname. @default true */
   rejectInvalidHostnames?: boolean;
+  /**
+   * PEM client certificate chain for mutual TLS (leaf first). Requires `key`.
+   * PKCS#12 (`pfx`) is not supported by the rustls backend.
+   */
+  cert?: string | Buffer;
+  /** PEM private key (PKCS#8, PKCS#1 or SEC1) matching `cert`. */
+  key?: string | Buffer;
 };
 
 /** Basic-auth credentials for an upstream proxy. */
@@ -32,6 +39,12 @@ export type ProxyOptions =
       uri: string;
       headers?: Record<string, string | string[]>;
       auth?: ProxyAuth;
+      /**
+       * Destinations that connect directly instead of through `uri`, in
+       * `NO_PROXY` syntax: `example.com` (and its subdomains), `.example.com`,
+       * IPs, or CIDRs like `10.0.0.0/8`. `"system"` reads `NO_PROXY` itself.
+       */
+      noProxy?: string[];
     };
 
 /** Agent configuration. All options have undici-compatible defaults. */
@@ -40,26 +53,131 @@ export type AgentOptions = {
   headersTimeout?: number;
   /** Time to wait between body chunks. @default 300_000 ms */
   bodyTimeout?: number;
-  /** TCP connect timeout. @default 10_000 ms */
+  /**
+   * TCP (and TLS handshake) connect timeout, applied per connection attempt
+   * so unreachable hosts fail fast. Time spent connecting still counts
+   * against `timeout`; the shorter of the two wins. @default 10_000 ms
+   */
   connectTimeout?: number;
+  /**
+   * Total per-request deadline covering connect, headers, and body. Fires
+   * as `HeadersTimeoutError` or `BodyTimeoutError` depending on the phase.
+   * @default unlimited
+   */
+  timeout?: number;
   /** Idle keep-alive timeout. @default 4_000 ms */
   keepAliveTimeout?: number;
+  /**
+   * Max idle keep-alive connections kept per host; `0` disables reuse.
+   * @default unbounded (reqwest default)
+   */
+  poolMaxIdlePerHost?: number;
   /**
    * Max redirect hops. **Default is `0`** to match undici. `fetch()` performs
    * its own redirect handling; raw `request()`/`dispatch()` callers must set
    * this to follow redirects.
    */
   maxRedirections?: number;
+  /**
+   * Max requests executing at once across all origins; further dispatches
+   * queue in FIFO order. Time spent queued doesn't count against
+   * `headersTimeout`. @default unlimited
+   */
+  maxConcurrent?: number;
+  /**
+   * Max dispatches waiting for a `maxConcurrent` slot; beyond it they fail
+   * with `QueueFullError`. Requires `maxConcurrent`. @default unbounded
+   */
+  maxQueue?: number;
   /** Cap on decoded body in bytes. @default unlimited */
   maxResponseSize?: number;
   /** Cap on buffered Node `Readable` request bodies in bytes. @default 100 MiB */
   maxBufferedRequestBodyBytes?: number;
+  /**
+   * Keep an in-memory cookie jar: `Set-Cookie` responses are stored and
+   * replayed on later requests through this Agent. @default false
+   */
+  cookies?: boolean;
+  /**
+   * Transparently decode `Content-Encoding: gzip` responses. When disabled
+   * the encoded bytes and `content-encoding` header pass through untouched.
+   * Passing `true` to a build without the codec throws.
+   * @default true
+   */
+  gzip?: boolean;
+  /** Decode `Content-Encoding: br` responses (see `gzip`). @default true */
+  brotli?: boolean;
+  /** Decode `Content-Encoding: deflate` responses (see `gzip`). @default true */
+  deflate?: boolean;
+  /** Decode `Content-Encoding: zstd` responses (see `gzip`). @default true */
+  zstd?: boolean;
+  /** TCP keep-alive probe interval on pooled sockets. @default OS default */
+  tcpKeepAlive?: number;
+  /** Disable Nagle's algorithm (`TCP_NODELAY`). @default true */
+  tcpNoDelay?: boolean;
+  /**
+   * `User-Agent` sent when a request doesn't set its own.
+   * @default `node_reqwest/<semver>`, or `node_reqwest` for untagged builds
+   */
+  userAgent?: string;
+  /**
+   * Headers sent with every request through this Agent. A header of the
+   * same name set on an individual request replaces the default.
+   * @default {}
+   */
+  defaultHeaders?: Record<string, string | string[]>;
   /** Allow HTTP/2. @default true */
   allowH2?: boolean;
+  /**
+   * Skip ALPN and speak HTTP/2 from the first byte (including h2c over
+   * cleartext). For backends that mishandle negotiation; requires
+   * `allowH2`. @default false
+   */
+  http2PriorKnowledge?: boolean;
+  /**
+   * Send an HTTP/2 PING this often to keep long-lived connections alive
+   * through NAT and firewall idle timeouts. @default disabled
+   */
+  http2KeepAliveInterval?: number;
+  /**
+   * Close the connection if a PING isn't acknowledged within this time.
+   * Only applies with `http2KeepAliveInterval`. @default 20_000 ms
+   */
+  http2KeepAliveTimeout?: number;
+  /** Keep pinging connections with no open streams. @default false */
+  http2KeepAliveWhileIdle?: boolean;
+  /**
+   * Refuse to send anything over cleartext: requests to `http://` origins
+   * fail with `InvalidArgumentError`, and redirects to them with
+   * `RedirectError`. @default false
+   */
+  httpsOnly?: boolean;
   /** Source IP for outgoing connections. */
   localAddress?: string;
+  /**
+   * Pin hostnames to fixed IPs without touching DNS or `/etc/hosts`; TLS SNI
+   * and certificate checks still use `host`. Repeating a host lists several
+   * addresses to try. Connections always use the request URL's port, so
+   * `port` must be `0` or omitted; any other value throws.
+   */
+  resolve?: { host: string; port?: number; addr: string }[];
   /** TLS settings. */
   tls?: TlsOptions;
   /** Proxy configuration. */
   proxy?: ProxyOptions;
 };
+
+/**
+ * Point-in-time Agent counters from `agent.stats()`. Both are exact at the
+ * moment of the call. Idle pooled connections aren't included: reqwest keeps
+ * its pool private.
+ */
+export type AgentStats = {
+  /** Requests passed to `dispatch()` that haven't delivered a terminal callback. */
+  pending: number;
+  /**
+   * Requests held natively: queued for a `maxConcurrent` slot, connecting,
+   * waiting, or streaming.
+   */
+  inFlight: number;
+};
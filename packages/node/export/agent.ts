@@ -9,12 +9,13 @@ import { Dispatcher } from "undici";
 
 import { Addon } from "./addon.ts";
 import type {
+  AgentClientIdentity,
   AgentCreationOptions,
   AgentDispatchOptions,
   AgentHandle,
   AgentProxyOption,
 } from "./addon-def.ts";
-import type { AgentOptions, ProxyOptions, TlsOptions } from "./agent-def.ts";
+import type { AgentOptions, AgentStats, ProxyOptions, TlsOptions } from "./agent-def.ts";
 import { DispatchController, kSetRequestHandle } from "./dispatch-controller.ts";
 import {
   ClientClosedError,
@@ -32,16 +33,49 @@ const DEFAULT_MAX_BUFFERED_REQUEST_BODY_BYTES = 100 * 1024 * 1024;
 /** Wrap request ids before crossing the FFI to stay inside the Rust u32 range. */
 const REQUEST_ID_WRAP = 0xffff_ffff;
 
+const PEM_MARKER = Buffer.from("-----BEGIN");
+
+/**
+ * Wrap a DER-encoded certificate as a single PEM block so the Rust side
+ * keeps one parser (the 64-column base64 wrap matches RFC 7468).
+ */
+function derToPem(der: Buffer): string {
+  const lines = der.toString("base64").match(/.{1,64}/g) ?? [];
+  return `-----BEGIN CERTIFICATE-----\n${lines.join("\n")}\n-----END CERTIFICATE-----\n`;
+}
+
 /**
  * Coerce the `tls.ca` shape that undici accepts (`string | Buffer | array`)
- * into the flat string-array the FFI expects. Entry count and size caps are
- * enforced by the Rust side (rustls is the source of truth for PEM
- * validity).
+ * into the flat string-array the FFI expects. Buffers without a PEM marker
+ * are treated as DER. Entry count and size caps are enforced by the Rust
+ * side (rustls is the source of truth for PEM validity).
  */
 function normalizePem(pem?: string | Buffer | (string | Buffer)[]): string[] {
   if (!pem) return [];
   const list = Array.isArray(pem) ? pem : [pem];
-  return list.map((entry) => (Buffer.isBuffer(entry) ? entry.toString("utf8") : entry));
+  return list.map((entry) => {
+    if (!Buffer.isBuffer(entry)) return entry;
+    return entry.includes(PEM_MARKER) ? entry.toString("utf8") : derToPem(entry);
+  });
+}
+
+/**
+ * Pair `tls.cert` with `tls.key` for the FFI. Both must be PEM: unlike `ca`,
+ * there's no DER fallback since a bare key buffer carries no type marker.
+ */
+function normalizeIdentity(tls: TlsOptions): AgentClientIdentity | null {
+  if (tls.cert === undefined && tls.key === undefined) return null;
+  if (tls.cert === undefined || tls.key === undefined) {
+    throw new InvalidArgumentError("tls.cert and tls.key must be set together");
+  }
+  const toPem = (value: string | Buffer, name: string): string => {
+    const pem = Buffer.isBuffer(value) ? value.toString("utf8") : value;
+    if (typeof pem !== "string" || !pem.includes("-----BEGIN")) {
+      throw new InvalidArgumentError(`tls.${name} must be a PEM string or Buffer`);
+    }
+    return pem;
+  };
+  return { cert: toPem(tls.cert, "cert"), key: toPem(tls.key, "key") };
 }
 
 type HeaderValue = string | string[] | number | undefined;
@@ -84,6 +118,8 @@ type BodyInput =
   | string
   | Buffer
   | Uint8Array
+  | ArrayBuffer
+  | ArrayBufferView
   | Readable
   | ReadableStream<Uint8Array>
   | Iterable<Uint8Array>
@@ -100,14 +136,21 @@ type NormalizedBody = {
 
 const EMPTY_BODY: NormalizedBody = { bytes: null, pendingBytes: null, reader: null };
 
-function normalizeBodyDirect(body: string | Buffer | Uint8Array): NormalizedBody {
+// Binary inputs travel as a `Uint8Array` *view* over the caller's memory —
+// `new Uint8Array(typedArray)` would copy it. The FFI makes the body's only
+// copy while `agentDispatch` runs, so later mutation by the caller is safe.
+function normalizeBodyDirect(body: string | ArrayBuffer | ArrayBufferView): NormalizedBody {
+  let bytes: Uint8Array;
   if (typeof body === "string") {
-    return { bytes: new Uint8Array(Buffer.from(body, "utf8")), pendingBytes: null, reader: null };
-  }
-  if (Buffer.isBuffer(body)) {
-    return { bytes: new Uint8Array(body), pendingBytes: null, reader: null };
+    bytes = Buffer.from(body, "utf8");
+  } else if (body instanceof Uint8Array) {
+    bytes = body;
+  } else if (ArrayBuffer.isView(body)) {
+    bytes = new Uint8Array(body.buffer, body.byteOffset, body.byteLength);
+  } else {
+    bytes = new Uint8Array(body);
   }
-  return { bytes: body, pendingBytes: null, reader: null };
+  return { bytes, pendingBytes: null, reader: null };
 }
 
 // Eager drain (not pull-based): the per-chunk Rust↔JS round-trip path is
@@ -124,7 +167,7 @@ function normalizeBodyStreaming(body: ReadableStream<Uint8Array>): NormalizedBod
 
 function normalizeBody(body: BodyInput, maxBufferedBytes: number): NormalizedBody {
   if (body === undefined || body === null) return EMPTY_BODY;
-  if (typeof body === "string" || Buffer.isBuffer(body) || body instanceof Uint8Array) {
+  if (typeof body === "string" || body instanceof ArrayBuffer || ArrayBuffer.isView(body)) {
     return normalizeBodyDirect(body);
   }
   if (body instanceof Readable) return normalizeBodyBuffered(body, maxBufferedBytes);
@@ -139,7 +182,11 @@ function normalizeBody(body: BodyInput, maxBufferedBytes: number): NormalizedBod
   ) {
     return normalizeBodyBuffered(Readable.from(body), maxBufferedBytes);
   }
-  return EMPTY_BODY;
+  // Silently sending an empty body for e.g. a number or plain object would
+  // hide the caller's bug; mirror undici's own rejection message.
+  throw new InvalidArgumentError(
+    "body must be a string, a Buffer, a Readable stream, an iterable, or an async iterable",
+  );
 }
 
 async function drainReadable(stream: Readable, maxBytes: number): Promise<Uint8Array> {
@@ -171,6 +218,16 @@ async function drainReadable(stream: Readable, maxBytes: number): Promise<Uint8A
   return new Uint8Array(out.buffer, out.byteOffset, out.byteLength);
 }
 
+/** Reject values that `String()` would silently turn into `[object Object]`. */
+function queryScalar(key: string, value: unknown): string {
+  if (typeof value === "object" && value !== null) {
+    throw new InvalidArgumentError(
+      `query[${JSON.stringify(key)}]: nested objects are not supported`,
+    );
+  }
+  return String(value);
+}
+
 function encodeQuery(query: Record<string, unknown> | string | null | undefined): string {
   if (!query) return "";
   if (typeof query === "string") return new URLSearchParams(query).toString();
@@ -178,9 +235,9 @@ function encodeQuery(query: Record<string, unknown> | string | null | undefined)
   for (const [k, v] of Object.entries(query)) {
     if (v === undefined || v === null) continue;
     if (Array.isArray(v)) {
-      for (const item of v) params.append(k, String(item));
+      for (const item of v) params.append(k, queryScalar(k, item));
     } else {
-      params.append(k, String(v));
+      params.append(k, queryScalar(k, v));
     }
   }
   return params.toString();
@@ -203,21 +260,88 @@ function buildCreationOptions(options?: AgentOptions): AgentCreationOptions {
     }
   }
 
+  const allowH2 = options?.allowH2 ?? true;
+  const http2PriorKnowledge = options?.http2PriorKnowledge ?? false;
+  if (http2PriorKnowledge && !allowH2) {
+    throw new InvalidArgumentError("http2PriorKnowledge requires allowH2");
+  }
+
+  const resolve = (options?.resolve ?? []).map(({ host, port, addr }) => {
+    if (typeof host !== "string" || host === "") {
+      throw new InvalidArgumentError("resolve: host must be a non-empty string");
+    }
+    if (typeof addr !== "string" || isIP(addr) === 0) {
+      throw new InvalidArgumentError(`resolve (${host}): addr must be a valid IPv4/IPv6 address`);
+    }
+    if (port !== undefined && port !== 0) {
+      throw new InvalidArgumentError(
+        `resolve (${host}): port must be 0 or omitted; the request URL's port is always used`,
+      );
+    }
+    return { host, port: port ?? 0, addr };
+  });
+
+  const userAgent = options?.userAgent;
+  if (userAgent !== undefined && typeof userAgent !== "string") {
+    throw new InvalidArgumentError("userAgent must be a string");
+  }
+
+  const maxConcurrent = options?.maxConcurrent;
+  if (maxConcurrent !== undefined && (!Number.isInteger(maxConcurrent) || maxConcurrent < 1)) {
+    throw new InvalidArgumentError("maxConcurrent must be a positive integer");
+  }
+  const maxQueue = options?.maxQueue;
+  if (maxQueue !== undefined) {
+    if (!Number.isInteger(maxQueue) || maxQueue < 0) {
+      throw new InvalidArgumentError("maxQueue must be a non-negative integer");
+    }
+    if (maxConcurrent === undefined) {
+      throw new InvalidArgumentError("maxQueue requires maxConcurrent");
+    }
+  }
+
+  const poolMaxIdlePerHost = options?.poolMaxIdlePerHost;
+  if (
+    poolMaxIdlePerHost !== undefined &&
+    (!Number.isInteger(poolMaxIdlePerHost) || poolMaxIdlePerHost < 0)
+  ) {
+    throw new InvalidArgumentError("poolMaxIdlePerHost must be a non-negative integer");
+  }
+
   return {
-    allowH2: options?.allowH2 ?? true,
+    allowH2,
     autoSelectFamily: true,
     bodyTimeout: options?.bodyTimeout ?? 300_000,
+    brotli: options?.brotli ?? null,
     ca: normalizePem(tls.ca),
+    cookies: options?.cookies ?? false,
     connectTimeout: options?.connectTimeout ?? 10_000,
+    defaultHeaders: normalizeHeaders(options?.defaultHeaders),
+    deflate: options?.deflate ?? null,
+    gzip: options?.gzip ?? null,
     headersTimeout: options?.headersTimeout ?? 300_000,
+    http2KeepAliveInterval: options?.http2KeepAliveInterval ?? null,
+    http2KeepAliveTimeout: options?.http2KeepAliveTimeout ?? null,
+    http2KeepAliveWhileIdle: options?.http2KeepAliveWhileIdle ?? false,
+    http2PriorKnowledge,
+    httpsOnly: options?.httpsOnly ?? false,
+    identity: normalizeIdentity(tls),
     keepAliveTimeout: options?.keepAliveTimeout ?? 4_000,
     localAddress: options?.localAddress ?? null,
+    maxConcurrent: maxConcurrent ?? null,
+    maxQueue: maxQueue ?? null,
     maxRedirections: options?.maxRedirections ?? 0,
     maxResponseSize: options?.maxResponseSize ?? null,
+    poolMaxIdlePerHost: poolMaxIdlePerHost ?? null,
     proxy: normalizeProxy(options?.proxy),
     rejectInvalidHostnames,
     rejectUnauthorized,
-    timeout: null,
+    resolve,
+    tcpKeepAlive: options?.tcpKeepAlive ?? null,
+    tcpNoDelay: options?.tcpNoDelay ?? true,
+    timeout: options?.timeout ?? null,
+    userAgent: userAgent ?? null,
+    zstd: options?.zstd ?? null,
   };
 }
 
@@ -244,6 +368,7 @@ function normalizeProxy(proxy: ProxyOptions | undefined): AgentProxyOption {
     uri: proxy.uri,
     headers: customHeaders,
     auth: proxy.auth ? { username: proxy.auth.username, password: proxy.auth.password } : null,
+    noProxy: proxy.noProxy ?? [],
   };
 }
 
@@ -254,6 +379,8 @@ interface RequestState {
   originKey: string;
   requestConnected: boolean;
   handlerErrored: boolean;
+  /** Handed to Rust; false while a `Readable` body is still buffering. */
+  submitted: boolean;
 }
 
 export class Agent extends Dispatcher {
@@ -278,10 +405,10 @@ export class Agent extends Dispatcher {
       options?.maxBufferedRequestBodyBytes ?? DEFAULT_MAX_BUFFERED_REQUEST_BODY_BYTES;
 
     this.#agent = Addon.agentCreate(buildCreationOptions(options), {
-      onResponseStart: (id, statusCode, headers, statusMessage) => {
+      onResponseStart: (id, statusCode, headers, statusMessage, remoteAddress) => {
         const state = this.#pending.get(id);
         if (state !== undefined) {
-          this.#dispatchOnResponseStart(state, statusCode, headers, statusMessage);
+          this.#dispatchOnResponseStart(state, statusCode, headers, statusMessage, remoteAddress);
         }
       },
       onResponseData: (id, chunk) => {
@@ -306,6 +433,7 @@ export class Agent extends Dispatcher {
     statusCode: number,
     respHeaders: Record<string, string | string[]>,
     statusMessage: string,
+    remoteAddress: string | undefined,
   ): void {
     if (state.controller.aborted || state.handlerErrored) return;
     state.requestConnected = true;
@@ -316,7 +444,9 @@ export class Agent extends Dispatcher {
     }
 
     // `undici.fetch` reads response headers from `controller.rawHeaders`
-    // (Buffer pairs) only — populate eagerly so fetch sees them.
+    // (Buffer pairs) only — populate eagerly so fetch sees them. Rust hands
+    // over names in wire order with repeated values grouped under the first
+    // occurrence, so this is the multi-valued source of truth.
     const raw: Buffer[] = [];
     for (const name in respHeaders) {
       const value = respHeaders[name];
@@ -327,6 +457,7 @@ export class Agent extends Dispatcher {
       }
     }
     state.controller.rawHeaders = raw;
+    if (remoteAddress !== undefined) state.controller.remoteAddress = remoteAddress;
 
     try {
       state.handler.onResponseStart?.(state.controller, statusCode, respHeaders, statusMessage);
@@ -470,8 +601,10 @@ export class Agent extends Dispatcher {
     }
 
     let headers: Record<string, string>;
+    let query: string;
     try {
       headers = normalizeHeaders(options.headers as HeaderInput);
+      query = encodeQuery(options.query as Record<string, unknown> | string | null | undefined);
     } catch (e) {
       return bail(toError(e));
     }
@@ -488,7 +621,7 @@ export class Agent extends Dispatcher {
       // path would yield a malformed URL. Match undici/RFC 9112 by
       // ensuring a leading slash for origin-form request targets.
       path: options.path.startsWith("/") ? options.path : `/${options.path}`,
-      query: encodeQuery(options.query as Record<string, unknown> | string | null | undefined),
+      query,
     };
 
     const requestId = this.#allocateRequestId();
@@ -499,6 +632,7 @@ export class Agent extends Dispatcher {
       originKey: origin.origin,
       requestConnected: false,
       handlerErrored: false,
+      submitted: false,
     });
 
     if (normalizedBody.pendingBytes) {
@@ -538,6 +672,8 @@ export class Agent extends Dispatcher {
     controller: DispatchController,
     handler: Dispatcher.DispatchHandler,
   ): void {
+    const state = this.#pending.get(requestId);
+    if (state !== undefined) state.submitted = true;
     try {
       const handle = Addon.agentDispatch(this.#agent, dispatchOptions, requestId);
       controller[kSetRequestHandle](handle);
@@ -558,4 +694,24 @@ export class Agent extends Dispatcher {
     this.#closed = true;
     return (this.#destroyPromise ??= Addon.agentDestroy(this.#agent));
   }
+
+  /**
+   * Abort every outstanding request with `RequestAbortedError` (e.g. to cancel
+   * a batch once one member fails). The agent stays open; requests that
+   * already settled are unaffected.
+   */
+  abortAll(): void {
+    Addon.agentAbortAll(this.#agent);
+    // Requests still buffering a `Readable` body haven't reached Rust; the
+    // deferred submit sees `controller.aborted` and fails them instead.
+    for (const { controller, submitted } of this.#pending.values()) {
+      if (!submitted) controller.abort(new RequestAbortedError());
+    }
+  }
+
+  /** Snapshot of outstanding work, for diagnosing pool exhaustion. */
+  stats(): AgentStats {
+    const { inFlight } = Addon.agentStats(this.#agent);
+    return { pending: this.#pending.size, inFlight };
+  }
 }
@@ -17,8 +17,16 @@ export class DispatchController implements Dispatcher.DispatchController {
   #reason: Error | null = null;
   #requestHandle: RequestHandle | null = null;
   readonly #addon: Addon;
-  /** Flat `[name, value, name, value, ...]` Buffer pairs — read by `undici.fetch`. */
+  /**
+   * Flat `[name, value, name, value, ...]` Buffer pairs in wire order, one
+   * pair per value (repeated `Set-Cookie` stays distinct). Read by `undici.fetch`.
+   */
   rawHeaders?: Buffer[];
+  /**
+   * `ip:port` of the peer that served the response, set before
+   * `onResponseStart`. Left unset when the connector can't report it.
+   */
+  remoteAddress?: string;
 
   constructor(addon: Addon) {
     this.#addon = addon;
@@ -1,9 +1,10 @@
 // SPDX-License-Identifier: Apache-2.0 OR MIT
 
 //! Error surface: re-export undici's classes directly so `instanceof` checks
-//! work against consumer code that imports from `undici`. We add nothing —
+//! work against consumer code that imports from `undici`. We add little —
 //! undici 8 already provides every class we need, except `RedirectError`
 //! which it dropped; we shim that one to keep the discriminator round-trip.
+//! `QueueFullError` is the one code of our own, for the `maxQueue` limit.
 
 import { errors as undiciErrors } from "undici";
 
@@ -45,6 +46,18 @@ export class RedirectError extends UndiciError {
   }
 }
 
+/**
+ * node-reqwest's own discriminator for `maxQueue` overflow — undici has no
+ * Agent-wide queue, so there is no upstream class to reuse.
+ */
+export class QueueFullError extends UndiciError {
+  constructor(message = "Request queue is full") {
+    super(message);
+    this.name = "QueueFullError";
+    this.code = "UND_ERR_QUEUE_FULL";
+  }
+}
+
 export function createUndiciError(info: CoreErrorInfo): InstanceType<typeof UndiciError> {
   const { code, message, statusCode, body, headers } = info;
   switch (code) {
@@ -70,6 +83,8 @@ export function createUndiciError(info: CoreErrorInfo): InstanceType<typeof Undi
       return new NotSupportedError(message);
     case "UND_ERR_REDIRECT":
       return new RedirectError(message);
+    case "UND_ERR_QUEUE_FULL":
+      return new QueueFullError(message);
     case "UND_ERR_RESPONSE":
       return new ResponseError(message, statusCode ?? 500, {
         headers: headers ?? null,
@@ -1,7 +1,14 @@
 // SPDX-License-Identifier: Apache-2.0 OR MIT
 
 export { Agent } from "./agent.ts";
-export type { AgentOptions, ProxyAuth, ProxyOptions, TlsOptions } from "./agent-def.ts";
+export type { NativeVersion } from "./addon-def.ts";
+export type {
+  AgentOptions,
+  AgentStats,
+  ProxyAuth,
+  ProxyOptions,
+  TlsOptions,
+} from "./agent-def.ts";
 export {
   BodyTimeoutError,
   ClientClosedError,
@@ -11,9 +18,11 @@ export {
   HeadersTimeoutError,
   InvalidArgumentError,
   NotSupportedError,
+  QueueFullError,
   RedirectError,
   RequestAbortedError,
   ResponseError,
   SocketError,
   UndiciError,
 } from "./errors.ts";
+export { compareVersions, version } from "./version.ts";
@@ -0,0 +1,21 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+import { Addon } from "./addon.ts";
+import type { NativeVersion } from "./addon-def.ts";
+
+/**
+ * Version and commit of the native module actually loaded, for diagnosing
+ * prebuilt-binary mismatches.
+ */
+export function version(): NativeVersion {
+  return Addon.version();
+}
+
+/**
+ * Semver precedence of two `vX.Y.Z[-pre][+build]` tags: `-1` if `a` sorts
+ * first, `1` if `b` does, `0` if they are equal (build metadata ignored).
+ * Throws on input that isn't a release tag, naming the offending string.
+ */
+export function compareVersions(a: string, b: string): -1 | 0 | 1 {
+  return Addon.compareVersions(a, b);
+}
@@ -4,26 +4,44 @@
 
 use std::collections::HashMap as StdHashMap;
 use std::net::IpAddr;
+use std::net::SocketAddr;
 use std::sync::Arc;
 use std::time::Duration;
 
 use neon::prelude::*;
 use nrcore::Agent;
 use nrcore::AgentConfig;
+use nrcore::ClientIdentity;
 use nrcore::CoreError;
 use nrcore::ProxyAuth;
 use nrcore::ProxyConfig;
 use nrcore::RequestController;
 
 use crate::dispatch::parse_dispatch_options;
+use crate::ffi_util::opt_bool;
 use crate::ffi_util::opt_size;
 use crate::ffi_util::opt_timeout_ms;
 use crate::handler::JsDispatchHandler;
 use crate::handler::SharedCallbacks;
 use crate::runtime_handle;
 
-/// Cap on proxy custom headers — bounded marshalling and `DoS` surface.
+// Caps on list-shaped options, checked before marshalling so a hostile or
+// runaway config can't make agent creation walk an unbounded JS array.
+// 64 is far above any real configuration.
+
+/// Proxy custom headers.
 const MAX_PROXY_HEADERS: u32 = 64;
+/// Agent default headers.
+const MAX_DEFAULT_HEADERS: u32 = 64;
+/// DNS override entries.
+const MAX_RESOLVE_ENTRIES: u32 = 64;
+/// Proxy bypass patterns.
+const MAX_NO_PROXY_ENTRIES: u32 = 64;
+
+/// Cap on a single PEM input: one `ca` entry or either identity half.
+const MAX_PEM_BYTES: usize = 256 * 1024;
+
+type ResolveMap = StdHashMap<String, Vec<SocketAddr>>;
 
 pub struct AgentHandle {
     pub inner: Arc<Agent>,
@@ -38,6 +56,78 @@ pub struct RequestHandle {
 
 impl Finalize for RequestHandle {}
 
+/// Read a `{ [name]: string }` object into a map, rejecting more than `max`
+/// entries. `label` names the option in the error message.
+fn parse_string_map<'cx>(
+    cx: &mut FunctionContext<'cx>,
+    obj: Handle<'cx, JsObject>,
+    label: &str,
+    max: u32,
+) -> NeonResult<StdHashMap<String, String>> {
+    let keys = obj.get_own_property_names(cx)?;
+    let len = keys.len(cx);
+    if len > max {
+        return cx.throw_error(format!("{label}: too many entries (max {max})"));
+    }
+    let mut map = StdHashMap::new();
+    for i in 0..len {
+        let key: Handle<'_, JsString> = keys.get(cx, i)?;
+        let key_str = key.value(cx);
+        let value: Handle<'_, JsString> = obj.get(cx, key)?;
+        map.insert(key_str, value.value(cx));
+    }
+    Ok(map)
+}
+
+/// Read `[{ host, port, addr }]` into host → addresses, keeping every entry
+/// for a repeated host so all of them are tried.
+fn parse_resolve<'cx>(
+    cx: &mut FunctionContext<'cx>,
+    list: Handle<'cx, JsArray>,
+) -> NeonResult<ResolveMap> {
+    let len = list.len(cx);
+    if len > MAX_RESOLVE_ENTRIES {
+        return cx.throw_error(format!(
+            "resolve: too many entries (max {MAX_RESOLVE_ENTRIES})"
+        ));
+    }
+    let mut resolve = ResolveMap::new();
+    for i in 0..len {
+        let entry: Handle<'_, JsObject> = list.get(cx, i)?;
+        let host: Handle<'_, JsString> = entry.get(cx, "host")?;
+        let host = host.value(cx);
+        if host.is_empty() {
+            return cx.throw_error(format!("resolve[{i}]: host must be a non-empty string"));
+        }
+        let addr: Handle<'_, JsString> = entry.get(cx, "addr")?;
+        let Ok(ip) = addr.value(cx).parse::<IpAddr>() else {
+            return cx.throw_error(format!("resolve[{i}] ({host}): addr must be an IP address"));
+        };
+        // reqwest always dials the request URL's port, so any other value
+        // would be silently ignored; `!= 0.0` also rejects fractions.
+        let port: Handle<'_, JsNumber> = entry.get(cx, "port")?;
+        if port.value(cx) != 0.0 {
+            return cx.throw_error(format!(
+                "resolve[{i}] ({host}): port must be 0; the request URL's port is always used"
+            ));
+        }
+        resolve
+            .entry(host)
+            .or_default()
+            .push(SocketAddr::new(ip, 0));
+    }
+    Ok(resolve)
+}
+
+/// `node_reqwest/<semver>` for release builds. Dirty or untagged builds have
+/// no version worth advertising, so they send the bare product token.
+fn default_user_agent() -> String {
+    meta::SEMVER.map_or_else(
+        || "node_reqwest".to_string(),
+        |version| format!("node_reqwest/{version}"),
+    )
+}
+
 fn parse_proxy<'cx>(
     cx: &mut FunctionContext<'cx>,
     obj: Handle<'cx, JsObject>,
@@ -54,29 +144,50 @@ fn parse_proxy<'cx>(
             }
 
             let headers_obj: Handle<'_, JsObject> = obj.get(cx, "headers")?;
-            let keys = headers_obj.get_own_property_names(cx)?;
-            let len = keys.len(cx);
-            if len > MAX_PROXY_HEADERS {
-                return cx.throw_error(format!(
-                    "proxy.headers: too many entries (max {MAX_PROXY_HEADERS})"
-                ));
-            }
-            let mut headers = StdHashMap::new();
-            for i in 0..len {
-                let key: Handle<'_, JsString> = keys.get(cx, i)?;
-                let key_str = key.value(cx);
-                let value: Handle<'_, JsString> = headers_obj.get(cx, key)?;
-                headers.insert(key_str, value.value(cx));
-            }
+            let headers = parse_string_map(cx, headers_obj, "proxy.headers", MAX_PROXY_HEADERS)?;
 
             let auth = parse_proxy_auth(cx, obj)?;
 
-            Ok(ProxyConfig::Custom { uri, headers, auth })
+            let no_proxy_list: Handle<'_, JsArray> = obj.get(cx, "noProxy")?;
+            let no_proxy = parse_no_proxy(cx, no_proxy_list)?;
+
+            Ok(ProxyConfig::Custom {
+                uri,
+                headers,
+                auth,
+                no_proxy,
+            })
         },
         other => cx.throw_error(format!("invalid proxy.type: {other}")),
     }
 }
 
+/// Read `proxy.noProxy` patterns. Commas would be split again by the
+/// `NO_PROXY` parser, so they're rejected rather than silently widening a rule.
+fn parse_no_proxy<'cx>(
+    cx: &mut FunctionContext<'cx>,
+    list: Handle<'cx, JsArray>,
+) -> NeonResult<Vec<String>> {
+    let len = list.len(cx);
+    if len > MAX_NO_PROXY_ENTRIES {
+        return cx.throw_error(format!(
+            "proxy.noProxy: too many entries (max {MAX_NO_PROXY_ENTRIES})"
+        ));
+    }
+    let mut patterns = Vec::with_capacity(len as usize);
+    for i in 0..len {
+        let pattern: Handle<'_, JsString> = list.get(cx, i)?;
+        let pattern = pattern.value(cx);
+        if pattern.trim().is_empty() || pattern.contains(',') {
+            return cx.throw_error(format!(
+                "proxy.noProxy[{i}]: expected a single host, domain, IP or CIDR"
+            ));
+        }
+        patterns.push(pattern);
+    }
+    Ok(patterns)
+}
+
 fn parse_proxy_auth<'cx>(
     cx: &mut FunctionContext<'cx>,
     obj: Handle<'cx, JsObject>,
@@ -94,6 +205,25 @@ fn parse_proxy_auth<'cx>(
     }))
 }
 
+fn parse_identity<'cx>(
+    cx: &mut FunctionContext<'cx>,
+    options: Handle<'cx, JsObject>,
+) -> NeonResult<Option<ClientIdentity>> {
+    let identity_val: Handle<'_, JsValue> = options.get(cx, "identity")?;
+    if identity_val.is_a::<JsNull, _>(cx) || identity_val.is_a::<JsUndefined, _>(cx) {
+        return Ok(None);
+    }
+    let identity_obj = identity_val.downcast_or_throw::<JsObject, _>(cx)?;
+    let cert: Handle<'_, JsString> = identity_obj.get(cx, "cert")?;
+    let key: Handle<'_, JsString> = identity_obj.get(cx, "key")?;
+    let cert = cert.value(cx);
+    let key = key.value(cx);
+    if cert.len() > MAX_PEM_BYTES || key.len() > MAX_PEM_BYTES {
+        return cx.throw_error("identity: entry too large (max 256 KiB)");
+    }
+    Ok(Some(ClientIdentity { cert, key }))
+}
+
 #[neon::export(name = "agentCreate", context)]
 fn agent_create<'cx>(
     cx: &mut FunctionContext<'cx>,
@@ -105,6 +235,9 @@ fn agent_create<'cx>(
     let body_timeout = opt_timeout_ms(cx, options, "bodyTimeout")?;
     let connect_timeout = opt_timeout_ms(cx, options, "connectTimeout")?;
     let keep_alive = opt_timeout_ms(cx, options, "keepAliveTimeout")?;
+    let tcp_keepalive = opt_timeout_ms(cx, options, "tcpKeepAlive")?;
+    let http2_keep_alive_interval = opt_timeout_ms(cx, options, "http2KeepAliveInterval")?;
+    let http2_keep_alive_timeout = opt_timeout_ms(cx, options, "http2KeepAliveTimeout")?;
 
     let max_redirections: Handle<'_, JsNumber> = options.get(cx, "maxRedirections")?;
     let Some(max_redirections) = num_traits::cast::<f64, u32>(max_redirections.value(cx).max(0.0))
@@ -113,9 +246,36 @@ fn agent_create<'cx>(
     };
 
     let max_response_size = opt_size(cx, options, "maxResponseSize")?;
+    let max_concurrent =
+        opt_size(cx, options, "maxConcurrent")?.map(|n| usize::try_from(n).unwrap_or(usize::MAX));
+    let max_queue =
+        opt_size(cx, options, "maxQueue")?.map(|n| usize::try_from(n).unwrap_or(usize::MAX));
+    let pool_max_idle_per_host = opt_size(cx, options, "poolMaxIdlePerHost")?
+        .map(|n| usize::try_from(n).unwrap_or(usize::MAX));
 
     let allow_h2: Handle<'_, JsBoolean> = options.get(cx, "allowH2")?;
     let allow_h2 = allow_h2.value(cx);
+    let http2_prior_knowledge: Handle<'_, JsBoolean> = options.get(cx, "http2PriorKnowledge")?;
+    let http2_prior_knowledge = http2_prior_knowledge.value(cx);
+    let http2_keep_alive_while_idle: Handle<'_, JsBoolean> =
+        options.get(cx, "http2KeepAliveWhileIdle")?;
+    let http2_keep_alive_while_idle = http2_keep_alive_while_idle.value(cx);
+
+    let cookies: Handle<'_, JsBoolean> = options.get(cx, "cookies")?;
+    let cookies = cookies.value(cx);
+
+    let https_only: Handle<'_, JsBoolean> = options.get(cx, "httpsOnly")?;
+    let https_only = https_only.value(cx);
+
+    // Unset codecs fall back to the core default (on iff compiled in).
+    let defaults = AgentConfig::default();
+    let gzip = opt_bool(cx, options, "gzip")?.unwrap_or(defaults.gzip);
+    let brotli = opt_bool(cx, options, "brotli")?.unwrap_or(defaults.brotli);
+    let deflate = opt_bool(cx, options, "deflate")?.unwrap_or(defaults.deflate);
+    let zstd = opt_bool(cx, options, "zstd")?.unwrap_or(defaults.zstd);
+
+    let tcp_nodelay: Handle<'_, JsBoolean> = options.get(cx, "tcpNoDelay")?;
+    let tcp_nodelay = tcp_nodelay.value(cx);
 
     let reject_unauthorized: Handle<'_, JsBoolean> = options.get(cx, "rejectUnauthorized")?;
     let reject_invalid_hostnames: Handle<'_, JsBoolean> =
@@ -132,12 +292,14 @@ fn agent_create<'cx>(
     for i in 0..ca_len {
         let pem: Handle<'_, JsString> = ca.get(cx, i)?;
         let pem_str = pem.value(cx);
-        if pem_str.len() > 256 * 1024 {
+        if pem_str.len() > MAX_PEM_BYTES {
             return cx.throw_error(format!("ca[{i}]: entry too large (max 256 KiB)"));
         }
         ca_pems.push(pem_str);
     }
 
+    let identity = parse_identity(cx, options)?;
+
     let local_address: Handle<'_, JsValue> = options.get(cx, "localAddress")?;
     let local_address: Option<IpAddr> =
         if local_address.is_a::<JsNull, _>(cx) || local_address.is_a::<JsUndefined, _>(cx) {
@@ -152,26 +314,63 @@ fn agent_create<'cx>(
             }
         };
 
+    let resolve: Handle<'_, JsArray> = options.get(cx, "resolve")?;
+    let resolve = parse_resolve(cx, resolve)?;
+
     let auto_select_family: Handle<'_, JsBoolean> = options.get(cx, "autoSelectFamily")?;
     let auto_select_family = auto_select_family.value(cx);
 
     let proxy_obj: Handle<'_, JsObject> = options.get(cx, "proxy")?;
     let proxy = parse_proxy(cx, proxy_obj)?;
 
+    let user_agent: Handle<'_, JsValue> = options.get(cx, "userAgent")?;
+    let user_agent = if user_agent.is_a::<JsNull, _>(cx) || user_agent.is_a::<JsUndefined, _>(cx) {
+        default_user_agent()
+    } else {
+        user_agent.downcast_or_throw::<JsString, _>(cx)?.value(cx)
+    };
+
+    let default_headers_obj: Handle<'_, JsObject> = options.get(cx, "defaultHeaders")?;
+    let default_headers = parse_string_map(
+        cx,
+        default_headers_obj,
+        "defaultHeaders",
+        MAX_DEFAULT_HEADERS,
+    )?;
+
     let config = AgentConfig {
         timeout: timeout.map(Duration::from_millis),
         headers_timeout: headers_timeout.map(Duration::from_millis),
         body_timeout: body_timeout.map(Duration::from_millis),
         connect_timeout: connect_timeout.map(Duration::from_millis),
         pool_idle_timeout: keep_alive.map(Duration::from_millis),
+        pool_max_idle_per_host,
+        tcp_keepalive: tcp_keepalive.map(Duration::from_millis),
+        tcp_nodelay,
         max_redirections,
         max_response_size,
+        max_concurrent,
+        max_queue,
+        https_only,
+        cookies,
         allow_h2,
+        http2_prior_knowledge,
+        http2_keep_alive_interval: http2_keep_alive_interval.map(Duration::from_millis),
+        http2_keep_alive_timeout: http2_keep_alive_timeout.map(Duration::from_millis),
+        http2_keep_alive_while_idle,
         auto_select_family,
         reject_unauthorized,
         reject_invalid_hostnames,
         ca: ca_pems,
+        identity,
         local_address,
+        resolve,
+        gzip,
+        brotli,
+        deflate,
+        zstd,
+        user_agent: Some(user_agent),
+        default_headers,
         proxy,
     };
 
@@ -230,6 +429,32 @@ fn agent_dispatch<'cx>(
     Ok(cx.boxed(RequestHandle { inner: controller }))
 }
 
+/// Point-in-time counters for diagnosing connection exhaustion. reqwest
+/// keeps its pool private, so idle-connection counts aren't available.
+#[neon::export(name = "agentStats", context)]
+fn agent_stats<'cx>(
+    cx: &mut FunctionContext<'cx>,
+    agent: Handle<'cx, JsBox<AgentHandle>>,
+) -> JsResult<'cx, JsObject> {
+    let in_flight = agent.inner.in_flight();
+    let obj = cx.empty_object();
+    #[expect(clippy::cast_precision_loss, reason = "count far below 2^53")]
+    let in_flight = cx.number(in_flight as f64);
+    obj.set(cx, "inFlight", in_flight)?;
+    Ok(obj)
+}
+
+/// Cancel every outstanding request; each settles with `UND_ERR_ABORTED`
+/// through its own callbacks. Unlike `agentDestroy` the agent stays usable.
+#[neon::export(name = "agentAbortAll", context)]
+fn agent_abort_all<'cx>(
+    cx: &mut FunctionContext<'cx>,
+    agent: Handle<'cx, JsBox<AgentHandle>>,
+) -> JsResult<'cx, JsUndefined> {
+    agent.inner.abort_all();
+    Ok(cx.undefined())
+}
+
 #[neon::export(name = "agentClose", context)]
 fn agent_close<'cx>(
     cx: &mut FunctionContext<'cx>,
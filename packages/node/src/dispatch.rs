@@ -52,8 +52,14 @@ pub fn parse_dispatch_options<'cx>(
     let headers_timeout = opt_timeout_ms(cx, obj, "headersTimeout")?;
     let body_timeout = opt_timeout_ms(cx, obj, "bodyTimeout")?;
 
-    // `bodyBytes` (materialized) is the fast path — one `Bytes` clone, no
-    // per-chunk Channel::send round-trip. `body` (reader) is the streaming path.
+    // `bodyBytes` (materialized) is the fast path — one `Bytes` copy, no
+    // per-chunk Channel::send round-trip. `body` (reader) is the streaming path:
+    // it goes out chunked unless the caller set `content-length`, which hyper
+    // honours as fixed-length framing.
+    // Borrowing the JS backing store instead isn't sound: the slice is only
+    // valid for this call, while the request outlives it on the runtime and
+    // the caller may mutate or detach the buffer meanwhile. So exactly one
+    // copy is made here; the JS side hands over views, never copies.
     let body_bytes_value: Handle<'_, JsValue> = obj.get(cx, "bodyBytes")?;
     let body = if !body_bytes_value.is_a::<JsNull, _>(cx)
         && !body_bytes_value.is_a::<JsUndefined, _>(cx)
@@ -35,6 +35,20 @@ pub fn opt_timeout_ms<'cx>(
     js_number_to_u64::<u64>(cx, n, key).map(Some)
 }
 
+/// Optional boolean. `null` / `undefined` → `None` so the Rust side can
+/// pick a default that depends on compiled-in features.
+pub fn opt_bool<'cx>(
+    cx: &mut FunctionContext<'cx>,
+    obj: Handle<'cx, JsObject>,
+    key: &str,
+) -> NeonResult<Option<bool>> {
+    let v: Handle<'_, JsValue> = obj.get(cx, key)?;
+    if v.is_a::<JsNull, _>(cx) || v.is_a::<JsUndefined, _>(cx) {
+        return Ok(None);
+    }
+    Ok(Some(v.downcast_or_throw::<JsBoolean, _>(cx)?.value(cx)))
+}
+
 /// Optional non-negative size. `null` / `undefined` → `None`; `0` is accepted
 /// (caps to zero bytes — pathological but not a misuse).
 pub fn opt_size<'cx>(
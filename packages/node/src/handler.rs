@@ -44,9 +44,11 @@ where
     });
 }
 
-fn headers_to_js<'a>(
+/// Insertion order of `headers` becomes the JS object's key order, which the
+/// JS side relies on to rebuild `rawHeaders`.
+fn headers_to_js<'a, 'h>(
     cx: &mut Cx<'a>,
-    headers: &HashMap<String, Vec<String>>,
+    headers: impl IntoIterator<Item = (&'h String, &'h Vec<String>)>,
 ) -> JsResult<'a, JsObject> {
     let obj = cx.empty_object();
     for (key, values) in headers {
@@ -88,10 +90,15 @@ impl DispatchHandler for JsDispatchHandler {
             status_code,
             status_message,
             headers,
+            remote_addr,
         } = response;
 
         fire_js_callback(&cbs.channel.clone(), "onResponseStart", move |cx| {
             let headers_obj = headers_to_js(cx, &headers)?;
+            let remote_addr: Handle<'_, JsValue> = match remote_addr {
+                Some(addr) => cx.string(addr.to_string()).upcast(),
+                None => cx.undefined().upcast(),
+            };
             cbs.on_start
                 .to_inner(cx)
                 .call_with(cx)
@@ -99,6 +106,7 @@ impl DispatchHandler for JsDispatchHandler {
                 .arg(cx.number(f64::from(status_code)))
                 .arg(headers_obj)
                 .arg(cx.string(&status_message))
+                .arg(remote_addr)
                 .exec(cx)
         });
     }
@@ -10,16 +10,17 @@ mod handler;
 
 use std::sync::OnceLock;
 
-use mimalloc::MiMalloc;
-
+#[cfg(feature = "mimalloc")]
 #[global_allocator]
-static GLOBAL: MiMalloc = MiMalloc;
+static GLOBAL: mimalloc::MiMalloc = mimalloc::MiMalloc;
 
 use neon::prelude::*;
 
 /// Process-singleton tokio runtime that drives every dispatch future. Also
 /// registered as neon's global executor so any future neon-side spawning
 /// lands on the same runtime. Initialized exactly once by `neon::main`.
+/// Worker count is tokio's default — one per CPU core, overridable with
+/// `TOKIO_WORKER_THREADS` — shared by every Agent.
 static TOKIO_RUNTIME: OnceLock<tokio::runtime::Runtime> = OnceLock::new();
 
 pub(crate) fn runtime_handle() -> tokio::runtime::Handle {
@@ -36,6 +37,39 @@ pub(crate) fn runtime_handle() -> tokio::runtime::Handle {
         .clone()
 }
 
+/// Identifies the loaded native build: `{ version, commit }` from `meta`.
+#[neon::export(name = "version", context)]
+fn version<'cx>(cx: &mut FunctionContext<'cx>) -> JsResult<'cx, JsObject> {
+    let obj = cx.empty_object();
+    let version = cx.string(meta::VERSION);
+    obj.set(cx, "version", version)?;
+    let commit = cx.string(meta::COMMIT);
+    obj.set(cx, "commit", commit)?;
+    Ok(obj)
+}
+
+/// Semver precedence of two `vX.Y.Z` tags as `-1`, `0` or `1`, using the
+/// same parser that produces [`meta::SEMVER`].
+#[neon::export(name = "compareVersions", context)]
+fn compare_versions<'cx>(
+    cx: &mut FunctionContext<'cx>,
+    a: String,
+    b: String,
+) -> JsResult<'cx, JsNumber> {
+    let Some(lhs) = meta::Version::parse(&a) else {
+        return cx.throw_error(format!("compareVersions: invalid version {a:?}"));
+    };
+    let Some(rhs) = meta::Version::parse(&b) else {
+        return cx.throw_error(format!("compareVersions: invalid version {b:?}"));
+    };
+    let ordering = match lhs.cmp(&rhs) {
+        std::cmp::Ordering::Less => -1.0,
+        std::cmp::Ordering::Equal => 0.0,
+        std::cmp::Ordering::Greater => 1.0,
+    };
+    Ok(cx.number(ordering))
+}
+
 #[neon::main]
 fn main(mut cx: ModuleContext<'_>) -> NeonResult<()> {
     // Build the runtime here so a build failure propagates as a JS exception
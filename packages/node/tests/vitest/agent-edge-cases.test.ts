@@ -131,6 +131,27 @@ describe("Request body cap (Readable drain)", () => {
   });
 });
 
+describe("Unsupported request body types", () => {
+  beforeEach(() => {
+    agent = new Agent();
+  });
+
+  it.each([42, { key: "value" }, true])(
+    "rejects body %o with InvalidArgumentError",
+    async (body) => {
+      assert(agent);
+      const r = await dispatchOnce(agent, {
+        origin: "http://127.0.0.1:1",
+        path: "/",
+        method: "POST",
+        body: body as unknown as string,
+      });
+      expect(r.error).toBeInstanceOf(InvalidArgumentError);
+      expect(r.error?.message).toContain("body must be");
+    },
+  );
+});
+
 describe("Origin scheme guard", () => {
   beforeEach(() => {
     agent = new Agent();
@@ -15,6 +15,7 @@ import {
   HeadersTimeoutError,
   InvalidArgumentError,
   NotSupportedError,
+  QueueFullError,
   RedirectError,
   RequestAbortedError,
   ResponseError,
@@ -56,6 +57,7 @@ describe("Undici Error Classes", () => {
     ["UND_ERR_INVALID_ARG", InvalidArgumentError],
     ["UND_ERR_NOT_SUPPORTED", NotSupportedError],
     ["UND_ERR_REDIRECT", RedirectError],
+    ["UND_ERR_QUEUE_FULL", QueueFullError],
   ] as const)("maps %s → matching class", (code, Klass) => {
     const err = createUndiciError({ code, message: `case ${code}` });
     expect(err).toBeInstanceOf(Klass);
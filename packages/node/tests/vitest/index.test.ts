@@ -2,7 +2,7 @@
 
 import { describe, expect, it } from "vitest";
 import { Dispatcher } from "undici";
-import { Agent } from "../../export/index.ts";
+import { Agent, compareVersions, version } from "../../export/index.ts";
 
 describe("Agent export", () => {
   it("constructs and inherits from undici.Dispatcher", async () => {
@@ -12,3 +12,25 @@ describe("Agent export", () => {
     await agent.close();
   });
 });
+
+describe("version export", () => {
+  it("reports the native build identifiers", () => {
+    const { version: nativeVersion, commit } = version();
+    expect(nativeVersion).not.toBe("");
+    expect(commit).toMatch(/^([0-9a-f]{40}|undefined)$/);
+  });
+});
+
+describe("compareVersions export", () => {
+  it.each([
+    ["v1.2.3", "v1.10.0", -1],
+    ["v1.2.3", "v1.2.3+build.7", 0],
+    ["v1.0.0", "v1.0.0-rc.1", 1],
+  ] as const)("compares %s with %s", (a, b, expected) => {
+    expect(compareVersions(a, b)).toBe(expected);
+  });
+
+  it("throws naming the unparseable input", () => {
+    expect(() => compareVersions("v1.2.3", "1.2")).toThrow(/"1\.2"/);
+  });
+});